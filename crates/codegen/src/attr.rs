@@ -0,0 +1,304 @@
+use crate::util::{
+    find_attribute_value,
+    find_crate_name,
+};
+use heck::{
+    ToKebabCase,
+    ToLowerCamelCase,
+    ToShoutyKebabCase,
+    ToShoutySnakeCase,
+    ToSnakeCase,
+    ToUpperCamelCase,
+};
+use syn::{
+    Attribute,
+    Data,
+    DataEnum,
+    DataStruct,
+    DeriveInput,
+    Field,
+    Fields,
+    Ident,
+    Lit,
+    LitStr,
+    Meta,
+    MetaNameValue,
+    NestedMeta,
+    Path,
+    Type,
+};
+
+/// A field of the struct, with its `#[rustorm(...)]` attributes
+///
+/// ```rust,ignore
+/// #[derive(FromDao, ToDao, ToColumnNames, ToPrimaryKey)]
+/// #[rustorm(rename_all = "camelCase")]
+/// struct Actor {
+///     #[rustorm(primary_key, generated)]
+///     actor_id: i32,
+///     #[rustorm(rename = "name")]
+///     first_name: String,
+///     #[rustorm(skip)]
+///     films: Vec<Film>,
+///     #[rustorm(default)]
+///     rating: Option<i32>,
+///     #[rustorm(flatten)]
+///     audit: Audit,
+///     #[rustorm(with = "crate::point")]
+///     location: Point,
+///     #[rustorm(json)]
+///     address: Address,
+///     #[rustorm(read_only)]
+///     last_update: DateTime<Utc>,
+/// }
+/// ```
+pub struct FieldDef<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    /// the column name, after applying `rename` or the `rename_all` of the struct
+    pub column: LitStr,
+    /// the field is not persisted, it is set with `Default` when converted from a dao
+    pub skip: bool,
+    /// use `Default` when the column is absent
+    pub default: bool,
+    /// the columns of this field are embedded in the columns of the struct
+    pub flatten: bool,
+    /// the module with the `to_value` and `from_value` functions used to convert the field,
+    /// which is `rustorm::dao::json` for the fields stored as json
+    pub with: Option<Path>,
+    /// the column is part of the primary key
+    pub primary_key: bool,
+    /// the value is generated by the database when inserted
+    pub generated: bool,
+    /// the column is never written
+    pub read_only: bool,
+}
+
+/// parse the fields of the struct, the derive name is used in the panic messages
+pub fn parse_fields<'a>(ast: &'a DeriveInput, derive: &str) -> Vec<FieldDef<'a>> {
+    let fields = match ast.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(_) => {
+            panic!(
+                "#[derive({})] can only be used with structs that have named fields",
+                derive
+            )
+        }
+        Data::Enum(_) | Data::Union(_) => {
+            panic!("#[derive({})] can only be used with structs", derive)
+        }
+    };
+    let rename_all = parse_rename_all(&ast.attrs);
+    fields
+        .iter()
+        .map(|field| parse_field(field, rename_all))
+        .collect()
+}
+
+fn parse_field(field: &Field, rename_all: Option<RenameRule>) -> FieldDef<'_> {
+    let ident = field.ident.as_ref().expect("a named field");
+    let mut rename = find_attribute_value(&field.attrs, "column_name");
+    let mut field_def = FieldDef {
+        ident,
+        ty: &field.ty,
+        column: LitStr::new(&ident.to_string(), ident.span()),
+        skip: false,
+        default: false,
+        flatten: false,
+        with: None,
+        primary_key: false,
+        generated: false,
+        read_only: false,
+    };
+    for meta in rustorm_attributes(&field.attrs) {
+        match meta {
+            Meta::Path(ref path) if path.is_ident("skip") => field_def.skip = true,
+            Meta::Path(ref path) if path.is_ident("default") => field_def.default = true,
+            Meta::Path(ref path) if path.is_ident("flatten") => field_def.flatten = true,
+            Meta::Path(ref path) if path.is_ident("primary_key") => field_def.primary_key = true,
+            Meta::Path(ref path) if path.is_ident("generated") => field_def.generated = true,
+            Meta::Path(ref path) if path.is_ident("read_only") => field_def.read_only = true,
+            Meta::Path(ref path) if path.is_ident("json") => {
+                let rustorm = find_crate_name();
+                let json = syn::parse2(quote! { #rustorm::dao::json })
+                    .expect("json module path");
+                set_with(&mut field_def, json);
+            }
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref value),
+                ..
+            }) if path.is_ident("rename") => rename = Some(value.clone()),
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref value),
+                ..
+            }) if path.is_ident("with") => {
+                let with = value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid module path `{}`", value.value()));
+                set_with(&mut field_def, with);
+            }
+            _ => panic!("invalid `rustorm` attribute on the field `{}`", ident),
+        }
+    }
+    // the attributes of the embedded columns are in the flattened struct
+    if field_def.flatten
+        && (rename.is_some()
+            || field_def.with.is_some()
+            || field_def.default
+            || field_def.primary_key
+            || field_def.generated
+            || field_def.read_only)
+    {
+        panic!(
+            "the flattened field `{}` can not have other attributes than `skip`",
+            ident
+        );
+    }
+    field_def.column = match (rename, rename_all) {
+        (Some(rename), _) => rename,
+        (None, Some(rule)) => LitStr::new(&rule.apply(&ident.to_string()), ident.span()),
+        (None, None) => field_def.column,
+    };
+    field_def
+}
+
+/// A fieldless variant of an enum, with its `#[rustorm(rename = "...")]` attribute
+pub struct VariantDef<'a> {
+    pub ident: &'a Ident,
+    /// the text of the variant, after applying `rename` or the `rename_all` of the enum
+    pub name: LitStr,
+}
+
+/// parse the variants of a fieldless enum, the derive name is used in the panic messages
+pub fn parse_variants<'a>(
+    data: &'a DataEnum,
+    ast: &DeriveInput,
+    derive: &str,
+) -> Vec<VariantDef<'a>> {
+    let rename_all = parse_rename_all(&ast.attrs);
+    data.variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            if !matches!(variant.fields, Fields::Unit) {
+                panic!(
+                    "#[derive({})] can only be used with enums that have no fields, `{}` has",
+                    derive, ident
+                );
+            }
+            let mut rename = None;
+            for meta in rustorm_attributes(&variant.attrs) {
+                match meta {
+                    Meta::NameValue(MetaNameValue {
+                        ref path,
+                        lit: Lit::Str(ref value),
+                        ..
+                    }) if path.is_ident("rename") => rename = Some(value.clone()),
+                    _ => panic!("invalid `rustorm` attribute on the variant `{}`", ident),
+                }
+            }
+            let name = match (rename, rename_all) {
+                (Some(rename), _) => rename,
+                (None, Some(rule)) => LitStr::new(&rule.apply(&ident.to_string()), ident.span()),
+                (None, None) => LitStr::new(&ident.to_string(), ident.span()),
+            };
+            VariantDef { ident, name }
+        })
+        .collect()
+}
+
+/// `#[rustorm(json)]` is the same as `#[rustorm(with = "rustorm::dao::json")]`,
+/// so `to_dao` panics when the field can not be serialized into json
+fn set_with(field_def: &mut FieldDef, with: Path) {
+    if field_def.with.is_some() {
+        panic!(
+            "the field `{}` can only have one of the `with` and `json` attributes",
+            field_def.ident
+        );
+    }
+    field_def.with = Some(with);
+}
+
+fn parse_rename_all(attributes: &[Attribute]) -> Option<RenameRule> {
+    let mut rename_all = None;
+    for meta in rustorm_attributes(attributes) {
+        match meta {
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref value),
+                ..
+            }) if path.is_ident("rename_all") => {
+                rename_all = Some(RenameRule::from_str(&value.value()));
+            }
+            _ => panic!("invalid `rustorm` attribute on the struct"),
+        }
+    }
+    rename_all
+}
+
+/// the items of all the `#[rustorm(...)]` attributes
+fn rustorm_attributes(attributes: &[Attribute]) -> Vec<Meta> {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path.is_ident("rustorm"))
+        .flat_map(|attribute| {
+            match attribute.parse_meta() {
+                Ok(Meta::List(list)) => list.nested.into_iter(),
+                _ => panic!("invalid `rustorm` attribute, expecting `#[rustorm(...)]`"),
+            }
+        })
+        .map(|nested| {
+            match nested {
+                NestedMeta::Meta(meta) => meta,
+                NestedMeta::Lit(_) => panic!("invalid `rustorm` attribute"),
+            }
+        })
+        .collect()
+}
+
+/// The case conversion of `rename_all`, the same as in serde
+#[derive(Clone, Copy)]
+enum RenameRule {
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    fn from_str(rule: &str) -> Self {
+        match rule {
+            "lowercase" => RenameRule::LowerCase,
+            "UPPERCASE" => RenameRule::UpperCase,
+            "PascalCase" => RenameRule::PascalCase,
+            "camelCase" => RenameRule::CamelCase,
+            "snake_case" => RenameRule::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnakeCase,
+            "kebab-case" => RenameRule::KebabCase,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebabCase,
+            _ => panic!("unknown `rename_all` rule `{}`", rule),
+        }
+    }
+
+    fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::LowerCase => field.to_lowercase(),
+            RenameRule::UpperCase => field.to_uppercase(),
+            RenameRule::PascalCase => field.to_upper_camel_case(),
+            RenameRule::CamelCase => field.to_lower_camel_case(),
+            RenameRule::SnakeCase => field.to_snake_case(),
+            RenameRule::ScreamingSnakeCase => field.to_shouty_snake_case(),
+            RenameRule::KebabCase => field.to_kebab_case(),
+            RenameRule::ScreamingKebabCase => field.to_shouty_kebab_case(),
+        }
+    }
+}
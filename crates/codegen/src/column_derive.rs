@@ -1,54 +1,164 @@
-use crate::util::{
-    find_attribute_value,
-    find_crate_name,
-    parse_table_name,
+use crate::{
+    attr::{
+        parse_fields,
+        FieldDef,
+    },
+    util::{
+        find_crate_name,
+        parse_table_name,
+    },
 };
 use proc_macro2::TokenStream;
 use syn::{
-    Data,
     DeriveInput,
-    Field,
     LitStr,
 };
 
 pub fn impl_to_column_names(ast: &DeriveInput) -> TokenStream {
     let rustorm = find_crate_name();
     let name = &ast.ident;
-    let table_name = parse_table_name(&ast);
+    // the columns are qualified with the `schema.table` name
+    let table_name = parse_table_name(&ast).complete_name();
     let generics = &ast.generics;
+    let fields = parse_fields(ast, "ToColumnNames");
 
-    let from_fields = match ast.data {
-        Data::Struct(ref data) => {
-            data.fields
-                .iter()
-                .map(|field| generate_from_field(&rustorm, &table_name, field))
-        }
-        Data::Enum(_) | Data::Union(_) => {
-            panic!("#[derive(ToColumnNames)] can only be used with structs")
-        }
-    };
+    let columns = generate_columns(
+        &rustorm,
+        &table_name,
+        &fields,
+        &(quote! { #rustorm::dao::ToColumnNames }, quote! { to_column_names }),
+        |_| true,
+    );
 
     quote! {
         impl #generics #rustorm::dao::ToColumnNames for #name #generics {
             fn to_column_names() -> Vec<#rustorm::ColumnName> {
-                vec![
-                    #(#from_fields)*
-                ]
+                #columns
+            }
+        }
+    }
+}
+
+pub fn impl_to_primary_key(ast: &DeriveInput) -> TokenStream {
+    let rustorm = find_crate_name();
+    let name = &ast.ident;
+    let table_name = parse_table_name(&ast).complete_name();
+    let generics = &ast.generics;
+    let fields = parse_fields(ast, "ToPrimaryKey");
+
+    let primary_key = generate_columns(
+        &rustorm,
+        &table_name,
+        &fields,
+        &(quote! { #rustorm::dao::ToPrimaryKey }, quote! { to_primary_key }),
+        |field_def| field_def.primary_key,
+    );
+    let generated_columns = generate_columns(
+        &rustorm,
+        &table_name,
+        &fields,
+        &(quote! { #rustorm::dao::ToPrimaryKey }, quote! { to_generated_columns }),
+        |field_def| field_def.generated,
+    );
+    let read_only_columns = generate_columns(
+        &rustorm,
+        &table_name,
+        &fields,
+        &(quote! { #rustorm::dao::ToPrimaryKey }, quote! { to_read_only_columns }),
+        |field_def| field_def.read_only,
+    );
+
+    quote! {
+        impl #generics #rustorm::dao::ToPrimaryKey for #name #generics {
+            fn to_primary_key() -> Vec<#rustorm::ColumnName> {
+                #primary_key
+            }
+
+            fn to_generated_columns() -> Vec<#rustorm::ColumnName> {
+                #generated_columns
+            }
+
+            fn to_read_only_columns() -> Vec<#rustorm::ColumnName> {
+                #read_only_columns
+            }
+        }
+    }
+}
+
+/// the expression which collects the columns of the included fields.
+/// The columns of a flattened field are retrieved from its type using the `flattened`
+/// trait and function
+fn generate_columns<F>(
+    rustorm: &TokenStream,
+    table_name: &LitStr,
+    fields: &[FieldDef],
+    flattened: &(TokenStream, TokenStream),
+    include: F,
+) -> TokenStream
+where
+    F: Fn(&FieldDef) -> bool,
+{
+    let columns: Vec<TokenStream> = fields
+        .iter()
+        .filter(|field_def| !field_def.skip)
+        .filter_map(|field_def| {
+            if field_def.flatten {
+                Some(generate_flattened_columns(
+                    rustorm, table_name, field_def, flattened,
+                ))
+            } else if include(field_def) {
+                Some(generate_column(rustorm, table_name, field_def))
+            } else {
+                None
             }
+        })
+        .collect();
+
+    if columns.is_empty() {
+        quote! { vec![] }
+    } else {
+        quote! {
+            let mut columns = vec![];
+            #(#columns)*
+            columns
         }
     }
 }
 
-fn generate_from_field(rustorm: &TokenStream, table_name: &LitStr, field: &Field) -> TokenStream {
-    let field_name = field.ident.as_ref().unwrap();
-    let column_name = find_attribute_value(&field.attrs, "column_name")
-        .unwrap_or_else(|| LitStr::new(&field_name.to_string(), field_name.span()));
+fn generate_column(
+    rustorm: &TokenStream,
+    table_name: &LitStr,
+    field_def: &FieldDef,
+) -> TokenStream {
+    let column_name = &field_def.column;
 
     quote! {
-        #rustorm::ColumnName {
+        columns.push(#rustorm::ColumnName {
             name: #column_name.into(),
             table: Some(#table_name.to_owned()),
             alias: None,
-        },
+        });
+    }
+}
+
+fn generate_flattened_columns(
+    rustorm: &TokenStream,
+    table_name: &LitStr,
+    field_def: &FieldDef,
+    flattened: &(TokenStream, TokenStream),
+) -> TokenStream {
+    let ty = field_def.ty;
+    let (trait_path, function) = flattened;
+
+    // the embedded columns are in the table of this struct
+    quote! {
+        columns.extend(
+            <#ty as #trait_path>::#function()
+                .into_iter()
+                .map(|column| #rustorm::ColumnName {
+                    table: Some(#table_name.to_owned()),
+                    ..column
+                }),
+        );
     }
 }
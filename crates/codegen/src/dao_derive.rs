@@ -1,40 +1,74 @@
-use crate::util::{
-    find_attribute_value,
-    find_crate_name,
+use crate::{
+    attr::parse_fields,
+    util::find_crate_name,
 };
 use proc_macro2::TokenStream;
-use syn::{
-    Data,
-    DeriveInput,
-    Field,
-    Ident,
-    Lit,
-    LitStr,
-};
+use syn::DeriveInput;
 
+/// `FromDao` is implemented through `TryFromDao`, so both are generated
 pub fn impl_from_dao(ast: &DeriveInput) -> TokenStream {
     let rustorm = find_crate_name();
     let name = &ast.ident;
+    let try_from_dao = impl_try_from_dao(ast);
+
+    quote! {
+        #try_from_dao
 
-    let fields = match ast.data {
-        Data::Struct(ref data) => data.fields.iter().map(parse_field),
-        Data::Enum(_) | Data::Union(_) => {
-            panic!("#[derive(ToDao)] can only be used with structs")
+        impl #rustorm::dao::FromDao for  #name {
+
+            fn from_dao(dao: &#rustorm::Dao) -> Self {
+                match <Self as #rustorm::dao::TryFromDao>::try_from_dao(dao) {
+                    Ok(entity) => entity,
+                    Err(e) => panic!("{}", e),
+                }
+            }
         }
-    };
+    }
+}
+
+fn impl_try_from_dao(ast: &DeriveInput) -> TokenStream {
+    let rustorm = find_crate_name();
+    let name = &ast.ident;
+    let fields = parse_fields(ast, "FromDao");
 
-    let get_fields = fields.map(|(column_name, field_name)| {
-        quote! { #field_name: dao.get(#column_name).unwrap(),}
+    let entity_name = name.to_string();
+    let get_fields = fields.iter().map(|field_def| {
+        let field_name = field_def.ident;
+        let field = field_name.to_string();
+        let column_name = &field_def.column;
+        let ty = field_def.ty;
+        let get_field = match field_def.with {
+            Some(ref with) => {
+                quote! {
+                    dao.get_field_with(#entity_name, #field, #column_name, #with::from_value)?
+                }
+            }
+            None => quote! { dao.get_field(#entity_name, #field, #column_name)? },
+        };
+        let value = if field_def.skip {
+            quote! { Default::default() }
+        } else if field_def.flatten {
+            quote! { <#ty as #rustorm::dao::TryFromDao>::try_from_dao(dao)? }
+        } else if field_def.default {
+            quote! {
+                match dao.get_value(#column_name) {
+                    Some(_) => #get_field,
+                    None => Default::default(),
+                }
+            }
+        } else {
+            get_field
+        };
+        quote! { #field_name: #value,}
     });
 
     quote! {
-        impl #rustorm::dao::FromDao for  #name {
+        impl #rustorm::dao::TryFromDao for  #name {
 
-            fn from_dao(dao: &#rustorm::Dao) -> Self {
-                #name {
+            fn try_from_dao(dao: &#rustorm::Dao) -> Result<Self, #rustorm::dao::DaoError> {
+                Ok(#name {
                     #(#get_fields)*
-                }
-
+                })
             }
         }
     }
@@ -44,16 +78,18 @@ pub fn impl_to_dao(ast: &DeriveInput) -> TokenStream {
     let rustorm = find_crate_name();
     let name = &ast.ident;
     let generics = &ast.generics;
+    let fields = parse_fields(ast, "ToDao");
 
-    let fields = match ast.data {
-        Data::Struct(ref data) => data.fields.iter().map(parse_field),
-        Data::Enum(_) | Data::Union(_) => {
-            panic!("#[derive(ToDao)] can only be used with structs")
+    let insert_fields = fields.iter().filter(|field_def| !field_def.skip).map(|field_def| {
+        let field_name = field_def.ident;
+        let column_name = &field_def.column;
+        if field_def.flatten {
+            quote! { dao.0.extend(#rustorm::dao::ToDao::to_dao(&self.#field_name).0);}
+        } else if let Some(ref with) = field_def.with {
+            quote! { dao.insert_value(#column_name, &#with::to_value(&self.#field_name));}
+        } else {
+            quote! { dao.insert(#column_name, &self.#field_name);}
         }
-    };
-
-    let insert_fields = fields.map(|(column_name, field_name)| {
-        quote! { dao.insert(#column_name, &self.#field_name);}
     });
 
     quote! {
@@ -67,12 +103,3 @@ pub fn impl_to_dao(ast: &DeriveInput) -> TokenStream {
 
     }
 }
-
-fn parse_field(field: &Field) -> (Lit, &Ident) {
-    let field_name = field.ident.as_ref().unwrap();
-    let column_name = find_attribute_value(&field.attrs, "column_name")
-        .unwrap_or_else(|| LitStr::new(&field_name.to_string(), field_name.span()))
-        .into();
-
-    (column_name, field_name)
-}
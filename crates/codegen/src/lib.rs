@@ -7,6 +7,7 @@ extern crate quote;
 extern crate rustorm_dao;
 extern crate syn;
 
+mod attr;
 #[macro_use]
 mod column_derive;
 #[macro_use]
@@ -14,33 +15,56 @@ mod dao_derive;
 #[macro_use]
 mod table_derive;
 mod util;
+mod value_derive;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(FromDao, attributes(column_name))]
+/// derives `FromDao` and `TryFromDao` as well, there is no separate `TryFromDao` derive
+#[proc_macro_derive(FromDao, attributes(column_name, rustorm))]
 pub fn from_dao(tokens: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
 
     dao_derive::impl_from_dao(&input).into()
 }
 
-#[proc_macro_derive(ToDao, attributes(column_name))]
+#[proc_macro_derive(ToDao, attributes(column_name, rustorm))]
 pub fn to_dao(tokens: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
 
     dao_derive::impl_to_dao(&input).into()
 }
 
-#[proc_macro_derive(ToTableName, attributes(table_name))]
+#[proc_macro_derive(ToTableName, attributes(table_name, schema, rustorm))]
 pub fn to_table_name(tokens: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
 
     table_derive::impl_to_table_name(&input).into()
 }
 
-#[proc_macro_derive(ToColumnNames, attributes(column_name, table_name))]
+#[proc_macro_derive(ToColumnNames, attributes(column_name, table_name, schema, rustorm))]
 pub fn to_column_names(tokens: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
 
     column_derive::impl_to_column_names(&input).into()
 }
+
+#[proc_macro_derive(ToPrimaryKey, attributes(column_name, table_name, schema, rustorm))]
+pub fn to_primary_key(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+
+    column_derive::impl_to_primary_key(&input).into()
+}
+
+#[proc_macro_derive(ToValue, attributes(rustorm))]
+pub fn to_value(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+
+    value_derive::impl_to_value(&input).into()
+}
+
+#[proc_macro_derive(FromValue, attributes(rustorm))]
+pub fn from_value(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+
+    value_derive::impl_from_value(&input).into()
+}
@@ -9,13 +9,18 @@ pub fn impl_to_table_name(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
     let table_name = parse_table_name(&ast);
     let generics = &ast.generics;
+    let name_lit = &table_name.name;
+    let schema = match table_name.schema {
+        Some(ref schema) => quote! { Some(#schema.to_owned()) },
+        None => quote! { None },
+    };
 
     quote! {
         impl #generics #rustorm::dao::ToTableName for #name #generics {
             fn to_table_name() -> #rustorm::TableName {
                 #rustorm::TableName{
-                    name: #table_name.to_owned(),
-                    schema: None,
+                    name: #name_lit.to_owned(),
+                    schema: #schema,
                     alias: None,
                 }
             }
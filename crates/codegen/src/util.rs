@@ -72,16 +72,65 @@ pub fn find_attribute_value(attributes: &[Attribute], key: &str) -> Option<LitSt
         })
 }
 
+/// The table name of a [`DeriveInput`], with its schema if there is any.
+pub struct TableNameDef {
+    pub schema: Option<LitStr>,
+    pub name: LitStr,
+}
+
+impl TableNameDef {
+    /// The `schema.table` name, or just the table name if there is no schema.
+    pub fn complete_name(&self) -> LitStr {
+        match self.schema {
+            Some(ref schema) => {
+                LitStr::new(
+                    &format!("{}.{}", schema.value(), self.name.value()),
+                    self.name.span(),
+                )
+            }
+            None => self.name.clone(),
+        }
+    }
+}
+
 /// Discover the table name to use for the [`DeriveInput`].
 ///
 /// This is either the lower case type name or a custom name specified using a
-/// `#[table_name = "custom_name"]` attribute.
+/// `#[table_name = "custom_name"]` attribute. The schema is either specified in the table name
+/// as `#[table_name = "schema.custom_name"]` or with a `#[schema = "schema"]` attribute.
 ///
 /// # Panics
 ///
-/// If there's an invalid `table_name` attribute.
-pub fn parse_table_name(input: &DeriveInput) -> LitStr {
-    find_attribute_value(&input.attrs, "table_name").unwrap_or_else(|| {
+/// If there's an invalid `table_name` or `schema` attribute, or if the schema is specified in
+/// both.
+pub fn parse_table_name(input: &DeriveInput) -> TableNameDef {
+    let table_name = find_attribute_value(&input.attrs, "table_name").unwrap_or_else(|| {
         LitStr::new(&input.ident.to_string().to_snake_case(), input.ident.span())
-    })
+    });
+    let schema = find_attribute_value(&input.attrs, "schema");
+    let value = table_name.value();
+
+    match value.split_once('.') {
+        Some((table_schema, name)) => {
+            if table_schema.is_empty() || name.is_empty() || name.contains('.') {
+                panic!(
+                    "invalid `table_name` attribute `{}`, expecting `schema.table`",
+                    value
+                );
+            }
+            if schema.is_some() {
+                panic!("the schema is specified in both the `table_name` and `schema` attributes");
+            }
+            TableNameDef {
+                schema: Some(LitStr::new(table_schema, table_name.span())),
+                name: LitStr::new(name, table_name.span()),
+            }
+        }
+        None => {
+            TableNameDef {
+                schema,
+                name: table_name,
+            }
+        }
+    }
 }
@@ -0,0 +1,152 @@
+use crate::{
+    attr::parse_variants,
+    util::find_crate_name,
+};
+use proc_macro2::TokenStream;
+use syn::{
+    Data,
+    DataStruct,
+    DeriveInput,
+    Fields,
+    Type,
+    WhereClause,
+};
+
+/// an enum is converted to the text of its variant,
+/// and a newtype is converted the same way as the type it wraps
+pub fn impl_to_value(ast: &DeriveInput) -> TokenStream {
+    let rustorm = find_crate_name();
+    let name = &ast.ident;
+
+    let data = match ast.data {
+        Data::Enum(ref data) => data,
+        Data::Struct(ref data) => return impl_newtype_to_value(&rustorm, ast, data),
+        Data::Union(_) => panic!("#[derive(ToValue)] can only be used with enums and newtypes"),
+    };
+    let variants = parse_variants(data, ast, "ToValue");
+    let to_texts = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let text = &variant.name;
+        quote! { #name::#ident => #text, }
+    });
+    let texts = variants.iter().map(|variant| &variant.name);
+
+    quote! {
+        impl #rustorm::dao::ToValue for #name {
+            fn to_value(&self) -> #rustorm::dao::Value {
+                let text = match self {
+                    #(#to_texts)*
+                };
+                #rustorm::dao::Value::Text(text.to_string())
+            }
+        }
+
+        impl #rustorm::dao::ToEnumVariants for #name {
+            fn to_enum_variants() -> Vec<&'static str> {
+                vec![#(#texts),*]
+            }
+        }
+    }
+}
+
+/// an enum is converted from the text of its variant,
+/// and a newtype is converted the same way as the type it wraps
+pub fn impl_from_value(ast: &DeriveInput) -> TokenStream {
+    let rustorm = find_crate_name();
+    let name = &ast.ident;
+
+    let data = match ast.data {
+        Data::Enum(ref data) => data,
+        Data::Struct(ref data) => return impl_newtype_from_value(&rustorm, ast, data),
+        Data::Union(_) => panic!("#[derive(FromValue)] can only be used with enums and newtypes"),
+    };
+    let variants = parse_variants(data, ast, "FromValue");
+    let from_texts = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let text = &variant.name;
+        quote! { #text => Ok(#name::#ident), }
+    });
+    let enum_name = name.to_string();
+
+    quote! {
+        impl #rustorm::dao::FromValue for #name {
+            fn from_value(v: &#rustorm::dao::Value) -> Result<Self, #rustorm::dao::ConvertError> {
+                let text = <String as #rustorm::dao::FromValue>::from_value(v)?;
+                match text.as_str() {
+                    #(#from_texts)*
+                    _ => {
+                        Err(#rustorm::dao::ConvertError::UnknownVariant(
+                            #enum_name.to_string(),
+                            text,
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn impl_newtype_to_value(
+    rustorm: &TokenStream,
+    ast: &DeriveInput,
+    data: &DataStruct,
+) -> TokenStream {
+    let name = &ast.ident;
+    let inner = newtype_field(data, "ToValue");
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let where_clause =
+        extend_where_clause(where_clause, quote! { #inner: #rustorm::dao::ToValue });
+
+    quote! {
+        impl #impl_generics #rustorm::dao::ToValue for #name #ty_generics #where_clause {
+            fn to_value(&self) -> #rustorm::dao::Value {
+                #rustorm::dao::ToValue::to_value(&self.0)
+            }
+        }
+    }
+}
+
+fn impl_newtype_from_value(
+    rustorm: &TokenStream,
+    ast: &DeriveInput,
+    data: &DataStruct,
+) -> TokenStream {
+    let name = &ast.ident;
+    let inner = newtype_field(data, "FromValue");
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let where_clause =
+        extend_where_clause(where_clause, quote! { #inner: #rustorm::dao::FromValue });
+
+    quote! {
+        impl #impl_generics #rustorm::dao::FromValue for #name #ty_generics #where_clause {
+            fn from_value(v: &#rustorm::dao::Value) -> Result<Self, #rustorm::dao::ConvertError> {
+                <#inner as #rustorm::dao::FromValue>::from_value(v).map(#name)
+            }
+        }
+    }
+}
+
+/// the type of the only field of a tuple struct
+fn newtype_field<'a>(data: &'a DataStruct, derive: &str) -> &'a Type {
+    match data.fields {
+        Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+        _ => {
+            panic!(
+                "#[derive({})] can only be used with structs that have a single unnamed field",
+                derive
+            )
+        }
+    }
+}
+
+/// the where clause with the bound added, so a generic newtype can be converted
+/// whenever the type it wraps can
+fn extend_where_clause(where_clause: Option<&WhereClause>, bound: TokenStream) -> TokenStream {
+    match where_clause {
+        Some(where_clause) => {
+            let predicates = where_clause.predicates.iter();
+            quote! { where #(#predicates,)* #bound }
+        }
+        None => quote! { where #bound },
+    }
+}
@@ -3,6 +3,8 @@ fn is_keyword(s: &str) -> bool {
     keywords.contains(&s)
 }
 
+/// quote the few keywords that are commonly used as table names.
+/// This is not aware of the database platform, rustorm uses its `Dialect` instead
 pub fn keywords_safe(s: &str) -> String {
     if is_keyword(s) {
         format!("\"{}\"", s)
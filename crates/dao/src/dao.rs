@@ -1,5 +1,8 @@
 use crate::{
-    error::DaoError,
+    error::{
+        ConvertError,
+        DaoError,
+    },
     FromValue,
     ToValue,
     Value,
@@ -66,6 +69,45 @@ impl Dao {
         }
     }
 
+    /// get the value of the column for the field of the entity, the error names the entity,
+    /// the field, the column and its value.
+    /// This is used in the `TryFromDao` derived with `FromDao`.
+    pub fn get_field<T>(&self, entity: &str, field: &str, column: &str) -> Result<T, DaoError>
+    where
+        T: FromValue,
+    {
+        self.get_field_with(entity, field, column, T::from_value)
+    }
+
+    /// get the value of the column for the field of the entity, converted with `from_value`.
+    /// This is used in the `TryFromDao` derived with `FromDao` for the fields with
+    /// `#[rustorm(with = "...")]`.
+    pub fn get_field_with<T, F>(
+        &self,
+        entity: &str,
+        field: &str,
+        column: &str,
+        from_value: F,
+    ) -> Result<T, DaoError>
+    where
+        F: FnOnce(&Value) -> Result<T, ConvertError>,
+    {
+        let value = self.0.get(column);
+        let result = match value {
+            Some(v) => from_value(v).map_err(DaoError::ConvertError),
+            None => Err(DaoError::NoSuchValueError(column.into())),
+        };
+        result.map_err(|cause| {
+            DaoError::FieldError {
+                entity: entity.to_string(),
+                field: field.to_string(),
+                column: column.to_string(),
+                value: value.cloned(),
+                cause: Box::new(cause),
+            }
+        })
+    }
+
     pub fn get_value(&self, s: &str) -> Option<&Value> { self.0.get(s) }
 
     pub fn remove(&mut self, s: &str) -> Option<Value> { self.0.remove(s) }
@@ -95,6 +137,14 @@ pub trait FromDao {
     fn from_dao(dao: &Dao) -> Self;
 }
 
+/// The fallible counterpart of `FromDao`, a missing column or a value that can not be
+/// converted into the field type is returned as an error instead of panicking.
+pub trait TryFromDao: Sized {
+    /// convert dao to an instance of the corresponding struct of the model
+    /// taking into considerating the renamed columns
+    fn try_from_dao(dao: &Dao) -> Result<Self, DaoError>;
+}
+
 pub trait ToDao {
     /// convert from an instance of the struct to a dao representation
     /// to be saved into the database
@@ -151,6 +201,34 @@ mod tests {
         assert_eq!(life.unwrap(), 42);
     }
 
+    #[test]
+    fn get_field_error() {
+        let mut dao = Dao::new();
+        dao.insert("life", "forty two");
+        let life: Result<i32, DaoError> = dao.get_field("Answer", "life", "life");
+        match life {
+            Err(DaoError::FieldError {
+                entity,
+                field,
+                column,
+                value,
+                ..
+            }) => {
+                assert_eq!(entity, "Answer");
+                assert_eq!(field, "life");
+                assert_eq!(column, "life");
+                assert_eq!(value, Some(Value::Text("forty two".to_string())));
+            }
+            other => panic!("expecting a field error, got {:?}", other),
+        }
+        let lemons: Result<String, DaoError> = dao.get_field("Answer", "lemons", "lemons");
+        assert!(
+            matches!(lemons, Err(DaoError::FieldError { value: None, .. })),
+            "{:?}",
+            lemons
+        );
+    }
+
     #[test]
     fn referenced() {
         let mut dao = Dao::new();
@@ -1,9 +1,16 @@
+use crate::Value;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ConvertError {
     #[error("Conversion not supported {0} to {1}")]
     NotSupported(String, String),
+    #[error("Unknown variant {1} of the enum {0}")]
+    UnknownVariant(String, String),
+    #[error("Value {0} is out of the range of {1}")]
+    OutOfRange(String, String),
+    #[error("Value {0} can not be converted to {1} without losing precision")]
+    PrecisionLoss(String, String),
 }
 
 
@@ -13,4 +20,13 @@ pub enum DaoError {
     ConvertError(ConvertError),
     #[error("No such value {0}")]
     NoSuchValueError(String),
+    #[error("Unable to get the field {entity}.{field} from the column {column} with value {value:?}: {cause}")]
+    FieldError {
+        entity: String,
+        field: String,
+        column: String,
+        /// the value of the column, `None` if the column is missing
+        value: Option<Value>,
+        cause: Box<DaoError>,
+    },
 }
@@ -2,6 +2,7 @@ use serde_derive::{
     Deserialize,
     Serialize,
 };
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Interval {
@@ -19,3 +20,20 @@ impl Interval {
         }
     }
 }
+
+/// the interval in the ISO 8601 format, `P{months}M{days}DT{seconds}S`
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.microseconds < 0 { "-" } else { "" };
+        let microseconds = self.microseconds.unsigned_abs();
+        write!(
+            f,
+            "P{}M{}DT{}{}.{:06}S",
+            self.months,
+            self.days,
+            sign,
+            microseconds / 1_000_000,
+            microseconds % 1_000_000
+        )
+    }
+}
@@ -0,0 +1,153 @@
+//! Columns that are stored as json and converted from and into a rust type with serde.
+//! The functions of this module are used by the fields with `#[rustorm(json)]`.
+//! `ToValue` can not fail, so converting a value that serde can not serialize into json,
+//! such as a map with non string keys, panics. Use `try_to_value` to check it beforehand.
+use crate::{
+    ConvertError,
+    FromValue,
+    ToValue,
+    Value,
+};
+use serde::{
+    de::DeserializeOwned,
+    Serialize,
+};
+use std::ops::{
+    Deref,
+    DerefMut,
+};
+
+/// A value which is stored as json.
+/// This works with the json and jsonb columns of postgresql, the json columns of mysql
+/// and the text columns of sqlite.
+///
+/// # Panics
+/// `to_value` panics when the value can not be serialized into json, see `try_to_value`.
+///
+/// ```rust,ignore
+/// #[derive(FromDao, ToDao)]
+/// struct Customer {
+///     customer_id: i32,
+///     address: Json<Address>,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T { self.0 }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.0 }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(v: T) -> Self { Json(v) }
+}
+
+impl<T> ToValue for Json<T>
+where
+    T: Serialize,
+{
+    fn to_value(&self) -> Value { to_value(&self.0) }
+}
+
+impl<T> FromValue for Json<T>
+where
+    T: DeserializeOwned,
+{
+    fn from_value(v: &Value) -> Result<Self, ConvertError> { from_value(v).map(Json) }
+}
+
+/// serialize the value into a json value
+///
+/// # Panics
+/// when the value can not be serialized into json, such as a map with non string keys
+pub fn to_value<T>(v: &T) -> Value
+where
+    T: Serialize,
+{
+    try_to_value(v).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// serialize the value into a json value, or the error of serde
+pub fn try_to_value<T>(v: &T) -> Result<Value, ConvertError>
+where
+    T: Serialize,
+{
+    serde_json::to_string(v).map(Value::Json).map_err(|e| {
+        ConvertError::NotSupported(std::any::type_name::<T>().to_string(), format!("Json: {}", e))
+    })
+}
+
+/// deserialize the json, or the text containing json, into the value
+pub fn from_value<T>(v: &Value) -> Result<T, ConvertError>
+where
+    T: DeserializeOwned,
+{
+    let result = match *v {
+        Value::Json(ref json) | Value::Text(ref json) => serde_json::from_str(json),
+        Value::Blob(ref json) => serde_json::from_slice(json),
+        _ => {
+            return Err(ConvertError::NotSupported(
+                format!("{:?}", v),
+                "Json".to_string(),
+            ))
+        }
+    };
+    result.map_err(|e| ConvertError::NotSupported(format!("{:?}", v), format!("Json: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{
+        Deserialize,
+        Serialize,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let address = Json(Address {
+            city: "Lethbridge".to_string(),
+            zip: None,
+        });
+        let value = address.to_value();
+        assert_eq!(
+            value,
+            Value::Json(r#"{"city":"Lethbridge","zip":null}"#.to_string())
+        );
+        let from_json: Json<Address> = FromValue::from_value(&value).unwrap();
+        assert_eq!(from_json, address);
+
+        let from_text: Json<Address> =
+            FromValue::from_value(&Value::Text(r#"{"city":"Lethbridge"}"#.to_string())).unwrap();
+        assert_eq!(from_text, address);
+
+        let invalid: Result<Json<Address>, _> =
+            FromValue::from_value(&Value::Text("Lethbridge".to_string()));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn unserializable_json() {
+        let mut map = std::collections::HashMap::new();
+        map.insert((1, 2), "point");
+        assert!(try_to_value(&map).is_err());
+        let result = std::panic::catch_unwind(|| Json(map).to_value());
+        assert!(result.is_err());
+    }
+}
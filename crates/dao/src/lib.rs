@@ -9,12 +9,18 @@ pub use dao::{
     Dao,
     FromDao,
     ToDao,
+    TryFromDao,
 };
 pub use error::{
     ConvertError,
     DaoError,
 };
 pub use interval::Interval;
+pub use json::Json;
+pub use primary_key::{
+    ToKeyValues,
+    ToPrimaryKey,
+};
 pub use rows::Rows;
 pub use table_name::{
     TableName,
@@ -23,6 +29,7 @@ pub use table_name::{
 pub use value::{
     Array,
     FromValue,
+    ToEnumVariants,
     ToValue,
     Value,
 };
@@ -32,6 +39,8 @@ mod common;
 mod dao;
 mod error;
 mod interval;
+pub mod json;
+mod primary_key;
 mod rows;
 mod table_name;
 pub mod value;
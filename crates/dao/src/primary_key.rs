@@ -0,0 +1,69 @@
+use crate::{
+    ColumnName,
+    ToValue,
+    Value,
+};
+
+/// The columns of an entity which are written differently than the others,
+/// derived from the `#[rustorm(primary_key)]`, `#[rustorm(generated)]` and
+/// `#[rustorm(read_only)]` attributes of the fields.
+pub trait ToPrimaryKey {
+    /// the columns of the primary key, in the order of the fields
+    fn to_primary_key() -> Vec<ColumnName>;
+
+    /// the columns which values are generated by the database when the record is inserted,
+    /// such as an auto increment key or a column with a default. They are left out of the
+    /// insert and retrieved afterwards.
+    fn to_generated_columns() -> Vec<ColumnName>;
+
+    /// the columns which are never written, only read
+    fn to_read_only_columns() -> Vec<ColumnName>;
+}
+
+/// The value of a primary key, which is used to look up a record.
+/// A composite key is expressed as a tuple, the values are in the same order
+/// as the columns of the primary key.
+pub trait ToKeyValues {
+    fn to_key_values(&self) -> Vec<Value>;
+}
+
+impl<T> ToKeyValues for T
+where
+    T: ToValue,
+{
+    fn to_key_values(&self) -> Vec<Value> { vec![self.to_value()] }
+}
+
+macro_rules! impl_to_key_values_for_tuple {
+    ($($ty: ident : $index: tt),*) => {
+        impl<$($ty),*> ToKeyValues for ($($ty,)*)
+        where
+            $($ty: ToValue,)*
+        {
+            fn to_key_values(&self) -> Vec<Value> { vec![$(self.$index.to_value()),*] }
+        }
+    };
+}
+
+impl_to_key_values_for_tuple!(A: 0, B: 1);
+impl_to_key_values_for_tuple!(A: 0, B: 1, C: 2);
+impl_to_key_values_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn single_and_composite_keys() {
+        assert_eq!(1.to_key_values(), vec![Value::Int(1)]);
+        assert_eq!((1, "a").to_key_values(), vec![
+            Value::Int(1),
+            Value::Text("a".to_string())
+        ]);
+        assert_eq!((1i64, 2i16, 'c').to_key_values(), vec![
+            Value::Bigint(1),
+            Value::Smallint(2),
+            Value::Char('c')
+        ]);
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
 };
 use bigdecimal::{
     BigDecimal,
+    FromPrimitive,
     ToPrimitive,
 };
 use chrono::{
@@ -19,7 +20,11 @@ use serde_derive::{
     Deserialize,
     Serialize,
 };
-use std::fmt;
+use std::{
+    convert::TryFrom,
+    fmt,
+    str::FromStr,
+};
 use uuid::Uuid;
 
 /// Generic value storage 32 byte in size
@@ -80,54 +85,55 @@ impl fmt::Display for Value {
             Value::Time(v) => write!(f, "{}", v),
             Value::DateTime(v) => write!(f, "{}", v.format("%Y-%m-%d %H:%M:%S").to_string()),
             Value::Timestamp(v) => write!(f, "{}", v.to_rfc3339()),
+            Value::Interval(v) => v.fmt(f),
+            Value::Point(v) => write!(f, "({},{})", v.x(), v.y()),
             Value::Array(array) => array.fmt(f),
             Value::Blob(v) => {
                 let encoded = base64::encode_config(&v, base64::STANDARD);
                 write!(f, "{}", encoded)
             }
-            _ => panic!("not yet implemented: {:?}", self),
         }
     }
 }
 
+/// The elements of an sql array, a `None` element is a null in the array
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Array {
-    /*
-    Bool(Vec<bool>),
-
-    Tinyint(Vec<i8>),
-    Smallint(Vec<i16>),
-    */
-    Int(Vec<i32>),
-    Float(Vec<f32>),
-    /*
-    Bigint(Vec<i64>),
-
-    Double(Vec<f64>),
-    BigDecimal(Vec<BigDecimal>),
-    */
-    Text(Vec<String>),
-    /*
-    Char(Vec<char>),
-    Uuid(Vec<Uuid>),
-    Date(Vec<NaiveDate>),
-    Timestamp(Vec<DateTime<Utc>>),
-    */
+    Bool(Vec<Option<bool>>),
+
+    Smallint(Vec<Option<i16>>),
+    Int(Vec<Option<i32>>),
+    Bigint(Vec<Option<i64>>),
+
+    Float(Vec<Option<f32>>),
+    Double(Vec<Option<f64>>),
+    BigDecimal(Vec<Option<BigDecimal>>),
+
+    Text(Vec<Option<String>>),
+
+    Uuid(Vec<Option<Uuid>>),
+    Date(Vec<Option<NaiveDate>>),
+    Timestamp(Vec<Option<DateTime<Utc>>>),
 }
 
 impl fmt::Display for Array {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Array::Text(texts) => {
-                let json_arr = serde_json::to_string(texts).expect("must serialize");
-                write!(f, "{}", json_arr)
-            }
-            Array::Float(floats) => {
-                let json_arr = serde_json::to_string(floats).expect("must serialize");
-                write!(f, "{}", json_arr)
-            }
-            _ => panic!("not yet implemented: {:?}", self),
+        // the array is displayed as a json array, with the nulls as `null`
+        let json_arr = match self {
+            Array::Bool(v) => serde_json::to_string(v),
+            Array::Smallint(v) => serde_json::to_string(v),
+            Array::Int(v) => serde_json::to_string(v),
+            Array::Bigint(v) => serde_json::to_string(v),
+            Array::Float(v) => serde_json::to_string(v),
+            Array::Double(v) => serde_json::to_string(v),
+            Array::BigDecimal(v) => serde_json::to_string(v),
+            Array::Text(v) => serde_json::to_string(v),
+            Array::Uuid(v) => serde_json::to_string(v),
+            Array::Date(v) => serde_json::to_string(v),
+            Array::Timestamp(v) => serde_json::to_string(v),
         }
+        .expect("must serialize");
+        write!(f, "{}", json_arr)
     }
 }
 
@@ -160,13 +166,16 @@ impl_to_value!(NaiveDate, Date);
 impl_to_value!(NaiveTime, Time);
 impl_to_value!(DateTime<Utc>, Timestamp);
 impl_to_value!(NaiveDateTime, DateTime);
+impl_to_value!(BigDecimal, BigDecimal);
+impl_to_value!(Interval, Interval);
+impl_to_value!(Point<f64>, Point);
 
-impl ToValue for &str {
-    fn to_value(&self) -> Value { Value::Text(self.to_string()) }
+impl ToValue for serde_json::Value {
+    fn to_value(&self) -> Value { Value::Json(self.to_string()) }
 }
 
-impl ToValue for Vec<String> {
-    fn to_value(&self) -> Value { Value::Array(Array::Text(self.to_owned())) }
+impl ToValue for &str {
+    fn to_value(&self) -> Value { Value::Text(self.to_string()) }
 }
 
 impl<T> ToValue for Option<T>
@@ -199,6 +208,12 @@ pub trait FromValue: Sized {
     fn from_value(v: &Value) -> Result<Self, ConvertError>;
 }
 
+/// The text of each variant of a rust enum that is stored as an sql enum,
+/// this is derived with `#[derive(ToValue)]` on an enum
+pub trait ToEnumVariants {
+    fn to_enum_variants() -> Vec<&'static str>;
+}
+
 macro_rules! impl_from_value {
     ($ty: ty, $ty_name: tt, $($variant: ident),*) => {
         /// try from to owned
@@ -214,14 +229,45 @@ macro_rules! impl_from_value {
     }
 }
 
-macro_rules! impl_from_value_numeric {
+/// the integers are converted from any integer or decimal that fits in the type
+macro_rules! impl_from_value_integer {
+    ($ty: ty, $method:ident, $ty_name: tt) => {
+        impl FromValue for $ty {
+            fn from_value(v: &Value) -> Result<Self, ConvertError> {
+                let out_of_range = || ConvertError::OutOfRange(format!("{:?}", v), $ty_name.into());
+                match *v {
+                    Value::Tinyint(i) => <$ty>::try_from(i).map_err(|_| out_of_range()),
+                    Value::Smallint(i) => <$ty>::try_from(i).map_err(|_| out_of_range()),
+                    Value::Int(i) => <$ty>::try_from(i).map_err(|_| out_of_range()),
+                    Value::Bigint(i) => <$ty>::try_from(i).map_err(|_| out_of_range()),
+                    Value::BigDecimal(ref d) => {
+                        if !d.is_integer() {
+                            return Err(ConvertError::PrecisionLoss(
+                                format!("{:?}", v),
+                                $ty_name.into(),
+                            ));
+                        }
+                        d.$method().ok_or_else(out_of_range)
+                    }
+                    _ => Err(ConvertError::NotSupported(format!("{:?}", v), $ty_name.into())),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_from_value_float {
     ($ty: ty, $method:ident, $ty_name: tt, $($variant: ident),*) => {
         impl FromValue for $ty {
             fn from_value(v: &Value) -> Result<Self, ConvertError> {
                 match *v {
                     $(Value::$variant(ref v) => Ok(v.to_owned() as $ty),
                     )*
-                    Value::BigDecimal(ref v) => Ok(v.$method().unwrap()),
+                    Value::BigDecimal(ref d) => {
+                        d.$method().filter(|f| f.is_finite()).ok_or_else(|| {
+                            ConvertError::OutOfRange(format!("{:?}", v), $ty_name.into())
+                        })
+                    }
                     _ => Err(ConvertError::NotSupported(format!("{:?}", v), $ty_name.into())),
                 }
             }
@@ -233,12 +279,132 @@ impl_from_value!(Vec<u8>, "Vec<u8>", Blob);
 impl_from_value!(char, "char", Char);
 impl_from_value!(Uuid, "Uuid", Uuid);
 impl_from_value!(NaiveDate, "NaiveDate", Date);
-impl_from_value_numeric!(i8, to_i8, "i8", Tinyint);
-impl_from_value_numeric!(i16, to_i16, "i16", Tinyint, Smallint);
-impl_from_value_numeric!(i32, to_i32, "i32", Tinyint, Smallint, Int, Bigint);
-impl_from_value_numeric!(i64, to_i64, "i64", Tinyint, Smallint, Int, Bigint);
-impl_from_value_numeric!(f32, to_f32, "f32", Float);
-impl_from_value_numeric!(f64, to_f64, "f64", Float, Double);
+impl_from_value!(Interval, "Interval", Interval);
+impl_from_value!(Point<f64>, "Point<f64>", Point);
+impl_from_value_integer!(i8, to_i8, "i8");
+impl_from_value_integer!(i16, to_i16, "i16");
+impl_from_value_integer!(i32, to_i32, "i32");
+impl_from_value_integer!(i64, to_i64, "i64");
+impl_from_value_integer!(u8, to_u8, "u8");
+impl_from_value_integer!(u16, to_u16, "u16");
+impl_from_value_integer!(u32, to_u32, "u32");
+impl_from_value_integer!(u64, to_u64, "u64");
+impl_from_value_float!(f32, to_f32, "f32", Float);
+impl_from_value_float!(f64, to_f64, "f64", Float, Double);
+
+/// integers are converted exactly, floats are converted to their shortest decimal representation
+impl FromValue for BigDecimal {
+    fn from_value(v: &Value) -> Result<Self, ConvertError> {
+        let not_supported = || ConvertError::NotSupported(format!("{:?}", v), "BigDecimal".into());
+        match *v {
+            Value::Tinyint(i) => Ok(BigDecimal::from(i)),
+            Value::Smallint(i) => Ok(BigDecimal::from(i)),
+            Value::Int(i) => Ok(BigDecimal::from(i)),
+            Value::Bigint(i) => Ok(BigDecimal::from(i)),
+            Value::Float(f) => BigDecimal::from_f32(f).ok_or_else(not_supported),
+            Value::Double(f) => BigDecimal::from_f64(f).ok_or_else(not_supported),
+            Value::BigDecimal(ref d) => Ok(d.to_owned()),
+            Value::Text(ref t) => BigDecimal::from_str(t).map_err(|_| not_supported()),
+            _ => Err(not_supported()),
+        }
+    }
+}
+
+impl FromValue for NaiveTime {
+    fn from_value(v: &Value) -> Result<Self, ConvertError> {
+        let not_supported = || ConvertError::NotSupported(format!("{:?}", v), "NaiveTime".into());
+        match *v {
+            Value::Time(t) => Ok(t),
+            Value::Text(ref t) => NaiveTime::from_str(t).map_err(|_| not_supported()),
+            _ => Err(not_supported()),
+        }
+    }
+}
+
+/// the json columns and the json texts are parsed, a null is `serde_json::Value::Null`
+impl FromValue for serde_json::Value {
+    fn from_value(v: &Value) -> Result<Self, ConvertError> {
+        match *v {
+            Value::Nil => Ok(serde_json::Value::Null),
+            Value::Json(ref j) | Value::Text(ref j) => {
+                serde_json::from_str(j).map_err(|e| {
+                    ConvertError::NotSupported(
+                        format!("{:?}", v),
+                        format!("serde_json::Value: {}", e),
+                    )
+                })
+            }
+            _ => {
+                Err(ConvertError::NotSupported(
+                    format!("{:?}", v),
+                    "serde_json::Value".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+/// `Vec<T>` and `Vec<Option<T>>` are converted to and from an array,
+/// a null element can only be converted into a `Vec<Option<T>>`
+macro_rules! impl_array {
+    ($ty: ty, $ty_name: tt, $array_variant: ident, $($variant: ident),*) => {
+        impl ToValue for Vec<$ty> {
+            fn to_value(&self) -> Value {
+                Value::Array(Array::$array_variant(
+                    self.iter().map(|v| Some(v.to_owned())).collect(),
+                ))
+            }
+        }
+
+        impl ToValue for Vec<Option<$ty>> {
+            fn to_value(&self) -> Value { Value::Array(Array::$array_variant(self.to_owned())) }
+        }
+
+        impl FromValue for Vec<Option<$ty>> {
+            fn from_value(v: &Value) -> Result<Self, ConvertError> {
+                match *v {
+                    $(Value::Array(Array::$variant(ref v)) => {
+                        Ok(v.iter().map(|v| v.as_ref().map(|v| v.to_owned() as $ty)).collect())
+                    }
+                    )*
+                    _ => {
+                        Err(ConvertError::NotSupported(
+                            format!("{:?}", v),
+                            concat!("Vec<Option<", $ty_name, ">>").into(),
+                        ))
+                    }
+                }
+            }
+        }
+
+        impl FromValue for Vec<$ty> {
+            fn from_value(v: &Value) -> Result<Self, ConvertError> {
+                let elements: Vec<Option<$ty>> = FromValue::from_value(v)?;
+                elements
+                    .into_iter()
+                    .collect::<Option<Vec<$ty>>>()
+                    .ok_or_else(|| {
+                        ConvertError::NotSupported(
+                            format!("{:?}", v),
+                            concat!("Vec<", $ty_name, ">").into(),
+                        )
+                    })
+            }
+        }
+    };
+}
+
+impl_array!(bool, "bool", Bool, Bool);
+impl_array!(i16, "i16", Smallint, Smallint);
+impl_array!(i32, "i32", Int, Smallint, Int);
+impl_array!(i64, "i64", Bigint, Smallint, Int, Bigint);
+impl_array!(f32, "f32", Float, Float);
+impl_array!(f64, "f64", Double, Float, Double);
+impl_array!(BigDecimal, "BigDecimal", BigDecimal, BigDecimal);
+impl_array!(String, "String", Text, Text);
+impl_array!(Uuid, "Uuid", Uuid, Uuid);
+impl_array!(NaiveDate, "NaiveDate", Date, Date);
+impl_array!(DateTime<Utc>, "DateTime", Timestamp, Timestamp);
 
 /// Char can be casted into String
 /// and they havea separate implementation for extracting data
@@ -266,20 +432,6 @@ impl FromValue for String {
     }
 }
 
-impl FromValue for Vec<String> {
-    fn from_value(v: &Value) -> Result<Self, ConvertError> {
-        match *v {
-            Value::Array(Array::Text(ref t)) => Ok(t.to_owned()),
-            _ => {
-                Err(ConvertError::NotSupported(
-                    format!("{:?}", v),
-                    "Vec<String>".to_string(),
-                ))
-            }
-        }
-    }
-}
-
 impl FromValue for bool {
     fn from_value(v: &Value) -> Result<Self, ConvertError> {
         match *v {
@@ -301,7 +453,7 @@ impl FromValue for bool {
 impl FromValue for DateTime<Utc> {
     fn from_value(v: &Value) -> Result<Self, ConvertError> {
         match *v {
-            Value::Text(ref v) => Ok(DateTime::<Utc>::from_utc(parse_naive_date_time(v), Utc)),
+            Value::Text(ref v) => Ok(DateTime::<Utc>::from_utc(parse_naive_date_time(v)?, Utc)),
             Value::DateTime(v) => Ok(DateTime::<Utc>::from_utc(v, Utc)),
             Value::Timestamp(v) => Ok(v),
             _ => {
@@ -317,7 +469,7 @@ impl FromValue for DateTime<Utc> {
 impl FromValue for NaiveDateTime {
     fn from_value(v: &Value) -> Result<Self, ConvertError> {
         match *v {
-            Value::Text(ref v) => Ok(parse_naive_date_time(v)),
+            Value::Text(ref v) => parse_naive_date_time(v),
             Value::DateTime(v) => Ok(v),
             _ => {
                 Err(ConvertError::NotSupported(
@@ -341,18 +493,11 @@ where
     }
 }
 
-fn parse_naive_date_time(v: &str) -> NaiveDateTime {
-    let ts = NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S");
-    if let Ok(ts) = ts {
-        ts
-    } else {
-        let ts = NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S%.3f");
-        if let Ok(ts) = ts {
-            ts
-        } else {
-            panic!("unable to parse timestamp: {}", v);
-        }
-    }
+fn parse_naive_date_time(v: &str) -> Result<NaiveDateTime, ConvertError> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(v, format).ok())
+        .ok_or_else(|| ConvertError::NotSupported(v.to_string(), "NaiveDateTime".to_string()))
 }
 
 #[cfg(test)]
@@ -386,6 +531,96 @@ mod tests {
         let _v6: Value = vec![1u8, 2, 255, 3].to_value();
     }
 
+    #[test]
+    fn array_to_and_from_value() {
+        let value = vec![1, 2, 3].to_value();
+        assert_eq!(value, Value::Array(Array::Int(vec![Some(1), Some(2), Some(3)])));
+        assert_eq!(Vec::<i32>::from_value(&value).unwrap(), vec![1, 2, 3]);
+        assert_eq!(Vec::<i64>::from_value(&value).unwrap(), vec![1, 2, 3]);
+        assert_eq!(value.to_string(), "[1,2,3]");
+
+        let value = vec![Some("a".to_string()), None].to_value();
+        assert_eq!(value.to_string(), r#"["a",null]"#);
+        assert_eq!(
+            Vec::<Option<String>>::from_value(&value).unwrap(),
+            vec![Some("a".to_string()), None]
+        );
+        assert!(Vec::<String>::from_value(&value).is_err());
+        assert!(Vec::<Uuid>::from_value(&value).is_err());
+    }
+
+    #[test]
+    fn integer_conversions() {
+        assert_eq!(i64::from_value(&Value::Int(42)).unwrap(), 42);
+        assert_eq!(u8::from_value(&Value::Bigint(255)).unwrap(), 255);
+        assert!(matches!(
+            u8::from_value(&Value::Int(256)),
+            Err(ConvertError::OutOfRange(..))
+        ));
+        assert!(matches!(
+            u32::from_value(&Value::Smallint(-1)),
+            Err(ConvertError::OutOfRange(..))
+        ));
+        assert!(matches!(
+            i32::from_value(&Value::Bigint(i64::MAX)),
+            Err(ConvertError::OutOfRange(..))
+        ));
+
+        let decimal = |d: &str| Value::BigDecimal(BigDecimal::from_str(d).unwrap());
+        assert_eq!(u64::from_value(&decimal("18446744073709551615")).unwrap(), u64::MAX);
+        assert_eq!(i16::from_value(&decimal("12.00")).unwrap(), 12);
+        assert!(matches!(
+            i16::from_value(&decimal("12.5")),
+            Err(ConvertError::PrecisionLoss(..))
+        ));
+        assert!(matches!(
+            i16::from_value(&decimal("40000")),
+            Err(ConvertError::OutOfRange(..))
+        ));
+    }
+
+    #[test]
+    fn other_conversions() {
+        assert_eq!(
+            BigDecimal::from_value(&Value::Double(0.1)).unwrap(),
+            BigDecimal::from_str("0.1").unwrap()
+        );
+        assert_eq!(
+            BigDecimal::from_value(&Value::Text("3.14".to_string())).unwrap(),
+            BigDecimal::from_str("3.14").unwrap()
+        );
+        let huge = Value::BigDecimal(BigDecimal::from_str("1e400").unwrap());
+        assert!(f64::from_value(&huge).is_err());
+        assert_eq!(
+            NaiveTime::from_value(&Value::Text("09:58:20".to_string())).unwrap(),
+            NaiveTime::from_hms_opt(9, 58, 20).unwrap()
+        );
+        let point = Point::new(1.0, 2.0);
+        assert_eq!(Point::<f64>::from_value(&point.to_value()).unwrap(), point);
+        let interval = Interval::new(1, 2, 3);
+        assert_eq!(Interval::from_value(&interval.to_value()).unwrap(), interval);
+        let json = serde_json::json!({"a": [1, null]});
+        assert_eq!(serde_json::Value::from_value(&json.to_value()).unwrap(), json);
+        assert_eq!(serde_json::Value::from_value(&Value::Nil).unwrap(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn display_all_values() {
+        let interval = Value::Interval(Interval::new(-1_500_000, 2, 14));
+        assert_eq!(interval.to_string(), "P14M2DT-1.500000S");
+        assert_eq!(Value::Point(Point::new(1.5, -2.0)).to_string(), "(1.5,-2)");
+        assert_eq!(Value::Nil.to_string(), "");
+    }
+
+    #[test]
+    fn invalid_date_time_text() {
+        let value = Value::Text("yesterday".to_string());
+        assert!(NaiveDateTime::from_value(&value).is_err());
+        assert!(DateTime::<Utc>::from_value(&value).is_err());
+        let value = Value::Text("2018-01-29 09:58:20.123456".to_string());
+        assert!(NaiveDateTime::from_value(&value).is_ok());
+    }
+
     #[test]
     fn naive_date_parse() {
         let v = "2018-01-29";
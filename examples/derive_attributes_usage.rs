@@ -0,0 +1,94 @@
+use rustorm::{
+    dao,
+    ColumnName,
+    Dao,
+    FromDao,
+    TableName,
+    ToColumnNames,
+    ToDao,
+    ToTableName,
+};
+
+/// stored as a text column in the form `x,y`
+#[derive(Debug, Default, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+mod point {
+    use super::Point;
+    use rustorm::{
+        ConvertError,
+        FromValue,
+        Value,
+    };
+
+    pub fn to_value(point: &Point) -> Value { Value::Text(format!("{},{}", point.x, point.y)) }
+
+    pub fn from_value(value: &Value) -> Result<Point, ConvertError> {
+        let text = String::from_value(value)?;
+        let invalid = || ConvertError::NotSupported(text.clone(), "Point".to_string());
+        let mut parts = text.split(',').map(|part| part.parse::<i32>());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point { x, y }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, FromDao, ToDao, ToColumnNames)]
+struct Audit {
+    created_by: String,
+    updated_by: Option<String>,
+}
+
+#[derive(Debug, FromDao, ToDao, ToTableName, ToColumnNames)]
+#[rustorm(rename_all = "camelCase")]
+struct Store {
+    store_id: i32,
+    #[rustorm(rename = "name")]
+    store_name: String,
+    #[rustorm(skip)]
+    visits: u32,
+    #[rustorm(default)]
+    opening_hours: Option<String>,
+    #[rustorm(flatten)]
+    audit: Audit,
+    #[rustorm(with = "point")]
+    location: Point,
+}
+
+fn main() {
+    use rustorm::dao::{
+        FromDao,
+        ToColumnNames,
+        ToDao,
+    };
+
+    let store = Store {
+        store_id: 1,
+        store_name: "Sakila".to_string(),
+        visits: 42,
+        opening_hours: None,
+        audit: Audit {
+            created_by: "ivanceras".to_string(),
+            updated_by: None,
+        },
+        location: Point { x: 3, y: 4 },
+    };
+    let mut dao = store.to_dao();
+    println!("dao: {:#?}", dao);
+    let columns: Vec<String> = Store::to_column_names()
+        .iter()
+        .map(|column| column.complete_name())
+        .collect();
+    println!("columns: {:?}", columns);
+
+    dao.remove("openingHours");
+    let retrieved = Store::from_dao(&dao);
+    println!("retrieved: {:#?}", retrieved);
+    assert_eq!(retrieved.visits, 0);
+    assert_eq!(retrieved.location, store.location);
+    assert_eq!(retrieved.audit.created_by, store.audit.created_by);
+}
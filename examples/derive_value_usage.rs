@@ -0,0 +1,57 @@
+use rustorm::{
+    dao,
+    Dao,
+    FromDao,
+    FromValue,
+    ToDao,
+    ToValue,
+    Uuid,
+};
+
+/// stored as the `mpaa_rating` enum
+#[derive(Debug, PartialEq, ToValue, FromValue)]
+enum Rating {
+    G,
+    #[rustorm(rename = "PG")]
+    Pg,
+    #[rustorm(rename = "PG-13")]
+    Pg13,
+}
+
+/// stored as the uuid it wraps
+#[derive(Debug, PartialEq, Clone, Copy, ToValue, FromValue)]
+struct CustomerId(Uuid);
+
+/// stored as the bigint it wraps
+#[derive(Debug, PartialEq, ToValue, FromValue)]
+struct Cents(i64);
+
+#[derive(Debug, PartialEq, FromDao, ToDao)]
+struct Rental {
+    customer_id: CustomerId,
+    rating: Rating,
+    amount: Cents,
+    discount: Option<Cents>,
+}
+
+fn main() {
+    use rustorm::dao::{
+        FromDao,
+        ToDao,
+        ToValue,
+    };
+
+    let rental = Rental {
+        customer_id: CustomerId(Uuid::new_v4()),
+        rating: Rating::Pg13,
+        amount: Cents(499),
+        discount: None,
+    };
+    let dao = rental.to_dao();
+    println!("dao: {:#?}", dao);
+    assert_eq!(rental, Rental::from_dao(&dao));
+
+    // the newtype can also be passed as a parameter,
+    // such as in `em.execute_sql_with_return(sql, &[&rental.customer_id])`
+    println!("param: {:?}", rental.customer_id.to_value());
+}
@@ -1,8 +1,10 @@
 use crate::{
     types::SqlType,
     ColumnName,
+    DataError,
     FromDao,
     TableName,
+    ToEnumVariants,
 };
 use uuid::Uuid;
 
@@ -17,7 +19,12 @@ pub struct ColumnDef {
 
 impl ColumnDef {
     /// check all the column constraint if any has AutoIncrement
-    pub fn is_autoincrement(&self) -> bool { self.autoincrement_sequence_name().is_some() }
+    pub fn is_autoincrement(&self) -> bool {
+        self.specification
+            .constraints
+            .iter()
+            .any(|c| matches!(c, ColumnConstraint::AutoIncrement(_)))
+    }
 
     /// get the sequnce name of this autoincrement column
     pub fn autoincrement_sequence_name(&self) -> Option<&String> {
@@ -46,6 +53,45 @@ impl ColumnDef {
 
     pub fn get_sql_type(&self) -> SqlType { self.specification.sql_type.clone() }
 
+    /// check that the variants of the rust enum are the same as the choices of this enum column,
+    /// or of the array of enum
+    pub fn check_enum<E>(&self) -> Result<(), DataError>
+    where
+        E: ToEnumVariants,
+    {
+        let choices = match self.specification.sql_type {
+            SqlType::Enum(_, ref choices) => choices,
+            SqlType::Array(ref sql_type) => {
+                match **sql_type {
+                    SqlType::Enum(_, ref choices) => choices,
+                    _ => return Err(DataError::NotAnEnum(self.name.complete_name())),
+                }
+            }
+            _ => return Err(DataError::NotAnEnum(self.name.complete_name())),
+        };
+        let variants = E::to_enum_variants();
+        let unknown: Vec<String> = variants
+            .iter()
+            .filter(|variant| !choices.iter().any(|choice| choice == *variant))
+            .map(|variant| variant.to_string())
+            .collect();
+        let missing: Vec<String> = choices
+            .iter()
+            .filter(|choice| !variants.contains(&choice.as_str()))
+            .cloned()
+            .collect();
+        if unknown.is_empty() && missing.is_empty() {
+            Ok(())
+        } else {
+            Err(DataError::EnumMismatch {
+                enum_name: std::any::type_name::<E>().to_string(),
+                column: self.name.complete_name(),
+                unknown,
+                missing,
+            })
+        }
+    }
+
     pub fn cast_as(&self) -> Option<SqlType> { self.get_sql_type().cast_as() }
 
     pub fn has_generated_default(&self) -> bool {
@@ -151,3 +197,137 @@ impl From<String> for Literal {
 impl<'a> From<&'a str> for Literal {
     fn from(s: &'a str) -> Self { Literal::String(String::from(s)) }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dao::{
+            FromDao,
+            ToDao,
+        },
+        ConvertError,
+        Dao,
+        FromValue,
+        ToValue,
+        Uuid,
+        Value,
+    };
+
+    #[derive(Debug, PartialEq, crate::ToValue, crate::FromValue)]
+    #[rustorm(rename_all = "UPPERCASE")]
+    enum Rating {
+        G,
+        Pg,
+        #[rustorm(rename = "PG-13")]
+        Pg13,
+    }
+
+    /// converted the same way as the uuid it wraps
+    #[derive(Debug, PartialEq, Clone, Copy, crate::ToValue, crate::FromValue)]
+    struct CustomerId(Uuid);
+
+    #[derive(Debug, PartialEq, crate::ToValue, crate::FromValue)]
+    struct Wrapped<T>(T)
+    where
+        T: Clone;
+
+    #[derive(Debug, PartialEq, crate::FromDao, crate::ToDao)]
+    struct Rental {
+        customer_id: CustomerId,
+        staff_id: Option<CustomerId>,
+        note: Wrapped<String>,
+    }
+
+    fn rating_column(choices: &[&str]) -> ColumnDef {
+        ColumnDef {
+            table: TableName::from("film"),
+            name: ColumnName::from("rating"),
+            comment: None,
+            specification: ColumnSpecification {
+                sql_type: SqlType::Enum(
+                    "mpaa_rating".into(),
+                    choices.iter().map(|choice| choice.to_string()).collect(),
+                ),
+                capacity: None,
+                constraints: vec![],
+            },
+            stat: None,
+        }
+    }
+
+    #[test]
+    fn enum_to_and_from_value() {
+        assert_eq!(Rating::Pg13.to_value(), Value::Text("PG-13".into()));
+        assert_eq!(Rating::Pg.to_value(), Value::Text("PG".into()));
+        assert_eq!(
+            Rating::from_value(&Value::Text("G".into())).unwrap(),
+            Rating::G
+        );
+        match Rating::from_value(&Value::Text("NC-17".into())) {
+            Err(ConvertError::UnknownVariant(name, variant)) => {
+                assert_eq!(name, "Rating");
+                assert_eq!(variant, "NC-17");
+            }
+            other => panic!("expecting an unknown variant, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn newtype_to_and_from_value() {
+        let uuid = Uuid::new_v4();
+        assert_eq!(CustomerId(uuid).to_value(), Value::Uuid(uuid));
+        assert_eq!(
+            CustomerId::from_value(&Value::Uuid(uuid)).unwrap(),
+            CustomerId(uuid)
+        );
+        assert!(CustomerId::from_value(&Value::Int(1)).is_err());
+        assert_eq!(
+            Wrapped("x".to_string()).to_value(),
+            Value::Text("x".into())
+        );
+        assert_eq!(
+            Wrapped::<i32>::from_value(&Value::Int(1)).unwrap(),
+            Wrapped(1)
+        );
+    }
+
+    #[test]
+    fn newtype_fields_round_trip() {
+        let rental = Rental {
+            customer_id: CustomerId(Uuid::new_v4()),
+            staff_id: None,
+            note: Wrapped("late".to_string()),
+        };
+        let dao = rental.to_dao();
+        assert_eq!(
+            dao.get_value("customer_id"),
+            Some(&Value::Uuid(rental.customer_id.0))
+        );
+        assert_eq!(dao.get_value("staff_id"), Some(&Value::Nil));
+        assert_eq!(Rental::from_dao(&dao), rental);
+
+        let mut dao = Dao::new();
+        let staff_id = Uuid::new_v4();
+        dao.insert("customer_id", rental.customer_id.0);
+        dao.insert("staff_id", staff_id);
+        dao.insert("note", "late");
+        assert_eq!(Rental::from_dao(&dao).staff_id, Some(CustomerId(staff_id)));
+    }
+
+    #[test]
+    fn check_enum_variants() {
+        assert!(rating_column(&["G", "PG", "PG-13"])
+            .check_enum::<Rating>()
+            .is_ok());
+        match rating_column(&["G", "PG-13", "NC-17"]).check_enum::<Rating>() {
+            Err(DataError::EnumMismatch {
+                unknown, missing, ..
+            }) => {
+                assert_eq!(unknown, vec!["PG"]);
+                assert_eq!(missing, vec!["NC-17"]);
+            }
+            other => panic!("expecting an enum mismatch, got {:?}", other),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     column::Capacity,
+    error::ConvertError,
     types::SqlType,
     Value,
 };
@@ -9,7 +10,7 @@ use bigdecimal::{
 };
 use chrono::NaiveDateTime;
 use log::*;
-use std::str::FromStr;
+use std::convert::TryFrom;
 
 pub fn extract_datatype_with_capacity(data_type: &str) -> (String, Option<Capacity>) {
     let start = data_type.find('(');
@@ -66,133 +67,113 @@ pub fn extract_datatype_with_capacity(data_type: &str) -> (String, Option<Capaci
     }
 }
 
-pub fn cast_type(value: &Value, required_type: &SqlType) -> Value {
+/// cast the value into the value of the required type, an error is returned if the value
+/// can not be represented in that type
+pub fn cast_type(value: &Value, required_type: &SqlType) -> Result<Value, ConvertError> {
     if *value == Value::Nil || required_type.same_type(value) {
-        value.to_owned()
-    } else {
-        match *value {
-            Value::Smallint(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Int => Value::Int(i32::from(v)),
-                    SqlType::Bigint => Value::Bigint(i64::from(v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+        return Ok(value.to_owned());
+    }
+    let casted = match *value {
+        Value::Smallint(v) => {
+            match *required_type {
+                SqlType::Tinyint => i8::try_from(v).ok().map(Value::Tinyint),
+                SqlType::Int => Some(Value::Int(i32::from(v))),
+                SqlType::Bigint => Some(Value::Bigint(i64::from(v))),
+                _ => None,
             }
-            Value::Int(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Smallint => Value::Smallint(v as i16),
-                    SqlType::Bigint => Value::Bigint(i64::from(v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+        }
+        Value::Int(v) => {
+            match *required_type {
+                SqlType::Tinyint => i8::try_from(v).ok().map(Value::Tinyint),
+                SqlType::Smallint => i16::try_from(v).ok().map(Value::Smallint),
+                SqlType::Bigint => Some(Value::Bigint(i64::from(v))),
+                _ => None,
             }
-            Value::Bigint(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Smallint => Value::Smallint(v as i16),
-                    SqlType::Int => Value::Int(v as i32),
-                    SqlType::Numeric => {
-                        let bigdecimal = BigDecimal::from_str(&format!("{}", v));
-                        assert!(bigdecimal.is_ok());
-                        Value::BigDecimal(bigdecimal.unwrap())
-                    }
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+        }
+        Value::Bigint(v) => {
+            match *required_type {
+                SqlType::Tinyint => i8::try_from(v).ok().map(Value::Tinyint),
+                SqlType::Smallint => i16::try_from(v).ok().map(Value::Smallint),
+                SqlType::Int => i32::try_from(v).ok().map(Value::Int),
+                SqlType::Numeric => Some(Value::BigDecimal(BigDecimal::from(v))),
+                SqlType::Varchar => Some(Value::Text(format!("{}", v))),
+                _ => None,
             }
-            Value::BigDecimal(ref v) => {
-                match *required_type {
-                    SqlType::Int => {
-                        let ival = v.to_i32();
-                        assert!(ival.is_some());
-                        let ival = ival.unwrap();
-                        Value::Int(ival)
-                    }
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+        }
+        Value::BigDecimal(ref v) => {
+            match *required_type {
+                SqlType::Int if v.is_integer() => v.to_i32().map(Value::Int),
+                SqlType::Varchar => Some(Value::Text(format!("{}", v))),
+                _ => None,
             }
-            Value::Text(ref v) => {
-                match *required_type {
-                    SqlType::Timestamp => {
-                        let ts = NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S");
-                        let ts = if let Ok(ts) = ts {
-                            ts
-                        } else {
-                            let ts = NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.3f");
-                            if let Ok(ts) = ts {
-                                ts
-                            } else {
-                                panic!("unable to parse timestamp: {}", v);
-                            }
-                        };
-                        Value::DateTime(ts)
-                    }
-                    SqlType::Char => {
-                        assert_eq!(v.len(), 1);
-                        Value::Char(v.chars().next().unwrap())
-                    }
-                    SqlType::Int => {
-                        if let Ok(v) = v.parse::<i32>() {
-                            Value::Int(v)
-                        } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
-                        }
-                    }
-                    // enums will be just Text
-                    SqlType::Enum(_, _) => Value::Text(v.to_string()),
-                    // ts vector is casted into text and then we just
-                    // return them as text as well
-                    SqlType::TsVector => Value::Text(v.to_string()),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
+        }
+        Value::Text(ref v) => {
+            match *required_type {
+                SqlType::Timestamp => {
+                    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M:%S%.3f"]
+                        .iter()
+                        .find_map(|format| NaiveDateTime::parse_from_str(v, format).ok())
+                        .map(Value::DateTime)
                 }
-            }
-            Value::Char(v) => {
-                match *required_type {
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
+                SqlType::Char => {
+                    let mut chars = v.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(Value::Char(c)),
+                        _ => None,
                     }
                 }
+                SqlType::Int => v.parse::<i32>().ok().map(Value::Int),
+                // enums will be just Text
+                SqlType::Enum(_, _) => Some(Value::Text(v.to_string())),
+                // ts vector is casted into text and then we just
+                // return them as text as well
+                SqlType::TsVector => Some(Value::Text(v.to_string())),
+                _ => None,
+            }
+        }
+        Value::Char(v) => {
+            match *required_type {
+                SqlType::Varchar => Some(Value::Text(format!("{}", v))),
+                _ => None,
             }
-            _ => {
-                panic!(
-                    "unsupported conversion from {:?} to {:?}",
-                    value, required_type
-                )
+        }
+        _ => None,
+    };
+    casted.ok_or_else(|| {
+        ConvertError::UnsupportedConversion {
+            value: format!("{:?}", value),
+            to: format!("{:?}", required_type),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cast_to_a_smaller_integer() {
+        assert_eq!(
+            cast_type(&Value::Bigint(42), &SqlType::Tinyint).unwrap(),
+            Value::Tinyint(42)
+        );
+        assert!(cast_type(&Value::Bigint(300), &SqlType::Tinyint).is_err());
+        assert!(cast_type(&Value::Int(i32::MAX), &SqlType::Smallint).is_err());
+    }
+
+    #[test]
+    fn unsupported_casts() {
+        let decimal = Value::BigDecimal(BigDecimal::from(3) / BigDecimal::from(2));
+        assert!(cast_type(&decimal, &SqlType::Int).is_err());
+        assert!(cast_type(&Value::Text("ab".to_string()), &SqlType::Char).is_err());
+        assert!(cast_type(&Value::Text("yesterday".to_string()), &SqlType::Timestamp).is_err());
+        assert!(cast_type(&Value::Text("1.5".to_string()), &SqlType::Int).is_err());
+        match cast_type(&Value::Bool(true), &SqlType::Uuid) {
+            Err(ConvertError::UnsupportedConversion { value, to }) => {
+                assert_eq!(value, "Bool(true)");
+                assert_eq!(to, "Uuid");
             }
+            other => panic!("expecting an unsupported conversion, got {:?}", other),
         }
     }
 }
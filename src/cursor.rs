@@ -0,0 +1,128 @@
+use crate::{
+    Database,
+    DbError,
+    Rows,
+    Value,
+};
+use log::*;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+/// the cursors are named uniquely since they are declared in the session of the connection
+static CURSOR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A server side cursor which fetches the records of a query in chunks of `fetch_size` rows.
+///
+/// The cursor is declared inside a transaction and reads from the snapshot of it,
+/// so it can page through a huge table without `OFFSET` scans. It is closed when it is
+/// exhausted or dropped, the connection is borrowed until then.
+///
+/// ```rust,ignore
+/// let mut tx = em.transaction()?;
+/// for rows in tx.declare_cursor("SELECT * FROM film", &[], 1000)? {
+///     let rows = rows?;
+///     ...
+/// }
+/// tx.commit()?;
+/// ```
+pub struct Cursor<'a> {
+    db: &'a mut dyn Database,
+    name: String,
+    fetch_size: usize,
+    closed: bool,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn declare(
+        db: &'a mut dyn Database,
+        sql: &str,
+        params: &[&Value],
+        fetch_size: usize,
+    ) -> Result<Self, DbError> {
+        check_fetch_size(fetch_size)?;
+        let name = format!(
+            "rustorm_cursor_{}",
+            CURSOR_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        db.execute_sql(
+            &format!("DECLARE {} NO SCROLL CURSOR FOR {}", name, sql),
+            params,
+        )?;
+        Ok(Cursor {
+            db,
+            name,
+            fetch_size,
+            closed: false,
+        })
+    }
+
+    pub fn fetch_size(&self) -> usize { self.fetch_size }
+
+    /// change the number of rows retrieved in the next fetches, which can not be 0
+    pub fn set_fetch_size(&mut self, fetch_size: usize) -> Result<(), DbError> {
+        check_fetch_size(fetch_size)?;
+        self.fetch_size = fetch_size;
+        Ok(())
+    }
+
+    /// fetch the next chunk of rows, `None` when all the rows are retrieved
+    pub fn fetch(&mut self) -> Result<Option<Rows>, DbError> {
+        if self.closed {
+            return Ok(None);
+        }
+        let sql = format!("FETCH FORWARD {} FROM {}", self.fetch_size, self.name);
+        let rows = match self.db.execute_sql_with_return(&sql, &[]) {
+            Ok(rows) => rows,
+            Err(e) => {
+                // the transaction is aborted, the cursor can no longer be closed
+                self.closed = true;
+                return Err(e);
+            }
+        };
+        // a chunk smaller than the fetch size is the last one
+        if rows.data.len() < self.fetch_size {
+            self.close_cursor()?;
+        }
+        if rows.data.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(rows))
+        }
+    }
+
+    /// close the cursor before all the rows are retrieved
+    pub fn close(mut self) -> Result<(), DbError> { self.close_cursor() }
+
+    fn close_cursor(&mut self) -> Result<(), DbError> {
+        if !self.closed {
+            self.closed = true;
+            self.db.execute_sql(&format!("CLOSE {}", self.name), &[])?;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = Result<Rows, DbError>;
+
+    fn next(&mut self) -> Option<Self::Item> { self.fetch().transpose() }
+}
+
+impl Drop for Cursor<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.close_cursor() {
+            error!("Unable to close the cursor {}: {}", self.name, e);
+        }
+    }
+}
+
+fn check_fetch_size(fetch_size: usize) -> Result<(), DbError> {
+    if fetch_size == 0 {
+        return Err(DbError::UnsupportedOperation(
+            "fetching 0 rows from a cursor".to_string(),
+        ));
+    }
+    Ok(())
+}
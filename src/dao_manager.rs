@@ -1,6 +1,7 @@
 use crate::{
     DBPlatform,
     Dao,
+    DaoIter,
     DataError,
     DbError,
     Rows,
@@ -37,6 +38,15 @@ impl DaoManager {
         Ok(daos)
     }
 
+    /// iterate over the records returned by the sql without holding all of them in memory
+    pub fn query_iter<'a>(
+        &'a mut self,
+        sql: &str,
+        params: &[&Value],
+    ) -> Result<DaoIter<'a>, DbError> {
+        self.0.query_iter(sql, params)
+    }
+
     pub fn execute_sql_with_one_return(
         &mut self,
         sql: &str,
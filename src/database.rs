@@ -5,10 +5,12 @@ use crate::db_auth::{
 };
 use crate::{
     table::SchemaContent,
+    Dao,
     DbError,
     Rows,
     TableDef,
     TableName,
+    TransactionOptions,
     Value,
 };
 use rustorm_codegen::FromDao;
@@ -21,15 +23,37 @@ pub struct DatabaseName {
     pub(crate) description: Option<String>,
 }
 
+/// The records of `Database::query_iter`, each record is retrieved from the database
+/// when the iterator is advanced
+pub type DaoIter<'a> = Box<dyn Iterator<Item = Result<Dao, DbError>> + 'a>;
+
 pub trait Database {
     fn begin_transaction(&mut self) -> Result<(), DbError>;
 
+    /// begin a transaction with the isolation level and access mode of the options,
+    /// returns `DbError::UnsupportedOperation` if the database can not honor them
+    fn begin_transaction_with_options(
+        &mut self,
+        options: &TransactionOptions,
+    ) -> Result<(), DbError>;
+
     fn commit_transaction(&mut self) -> Result<(), DbError>;
 
     fn rollback_transaction(&mut self) -> Result<(), DbError>;
 
+    /// the number of transactions that are open, the nested ones are savepoints
+    fn transaction_depth(&self) -> usize;
+
     fn execute_sql_with_return(&mut self, sql: &str, param: &[&Value]) -> Result<Rows, DbError>;
 
+    /// execute the sql and iterate over the returned records without holding all of them in
+    /// memory. The connection is borrowed until the iterator is dropped
+    fn query_iter<'a>(&'a mut self, sql: &str, param: &[&Value]) -> Result<DaoIter<'a>, DbError>;
+
+    /// execute an sql that doesn't return rows such as `UPDATE` and `DELETE`,
+    /// returns the number of affected rows
+    fn execute_sql(&mut self, sql: &str, param: &[&Value]) -> Result<u64, DbError>;
+
     fn get_table(&mut self, table_name: &TableName) -> Result<Option<TableDef>, DbError>;
 
     fn set_autoincrement_value(
@@ -60,3 +84,8 @@ pub trait Database {
     #[cfg(feature = "db-auth")]
     fn get_roles(&mut self, username: &str) -> Result<Vec<Role>, DbError>;
 }
+
+/// the record of a row with the values in the order of the columns
+pub(crate) fn to_dao(columns: &[String], values: Vec<Value>) -> Dao {
+    Dao(columns.iter().cloned().zip(values).collect())
+}
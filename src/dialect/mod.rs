@@ -0,0 +1,246 @@
+//! The sql differences between the supported database platforms.
+//! Every sql generated by rustorm goes through a `Dialect`, so the placeholders
+//! and identifiers are written in the form the database expects.
+use crate::UpsertAction;
+use rustorm_dao::{
+    ColumnName,
+    TableName,
+};
+
+pub use mysql::MysqlDialect;
+pub use postgres::PostgresDialect;
+pub use sqlite::SqliteDialect;
+
+mod mysql;
+mod postgres;
+mod sqlite;
+
+pub trait Dialect {
+    /// the placeholder of the bound param at `index`, starting at 1
+    fn placeholder(&self, index: usize) -> String;
+
+    /// the character used to quote an identifier, `"` in standard sql
+    fn identifier_quote(&self) -> char { '"' }
+
+    /// the keywords which can not be used as an identifier unless quoted,
+    /// in uppercase
+    fn reserved_words(&self) -> &'static [&'static str];
+
+    fn is_reserved(&self, ident: &str) -> bool {
+        self.reserved_words()
+            .iter()
+            .any(|word| word.eq_ignore_ascii_case(ident))
+    }
+
+    /// whether the identifier can be written as is, without being quoted
+    fn is_plain_identifier(&self, ident: &str) -> bool {
+        let mut chars = ident.chars();
+        match chars.next() {
+            Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+                chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !self.is_reserved(ident)
+            }
+            _ => false,
+        }
+    }
+
+    /// quote the identifier, any quote character in it is escaped by doubling it
+    fn quote_identifier(&self, ident: &str) -> String {
+        let quote = self.identifier_quote();
+        let escaped = ident.replace(quote, &format!("{}{}", quote, quote));
+        format!("{}{}{}", quote, escaped, quote)
+    }
+
+    /// the identifier, quoted only when needed
+    fn safe_identifier(&self, ident: &str) -> String {
+        if self.is_plain_identifier(ident) {
+            ident.to_string()
+        } else {
+            self.quote_identifier(ident)
+        }
+    }
+
+    /// the table name qualified with its schema if there is any
+    fn table_name(&self, table: &TableName) -> String {
+        match table.schema {
+            Some(ref schema) => {
+                format!(
+                    "{}.{}",
+                    self.safe_identifier(schema),
+                    self.safe_identifier(&table.name)
+                )
+            }
+            None => self.safe_identifier(&table.name),
+        }
+    }
+
+    /// the column name qualified with its table if there is any
+    fn column_name(&self, column: &ColumnName) -> String {
+        match column.table {
+            Some(ref table) => {
+                let mut qualified: Vec<String> = table
+                    .split('.')
+                    .map(|part| self.safe_identifier(part))
+                    .collect();
+                qualified.push(self.safe_identifier(&column.name));
+                qualified.join(".")
+            }
+            None => self.safe_identifier(&column.name),
+        }
+    }
+
+    fn bool_literal(&self, value: bool) -> &'static str {
+        if value {
+            "TRUE"
+        } else {
+            "FALSE"
+        }
+    }
+
+    /// the `LIMIT` and `OFFSET` clause, empty if there is neither
+    fn limit_offset(&self, limit: Option<usize>, offset: Option<usize>) -> String {
+        match (limit, offset) {
+            (Some(limit), Some(offset)) => format!("LIMIT {} OFFSET {}", limit, offset),
+            (Some(limit), None) => format!("LIMIT {}", limit),
+            (None, Some(offset)) => format!("OFFSET {}", offset),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// whether an array can be passed as a bound param, such as in `id = ANY($1)`
+    fn supports_array_params(&self) -> bool { false }
+
+    /// whether the inserted, updated or deleted rows can be retrieved
+    /// in the same statement with a `RETURNING` clause
+    fn supports_returning(&self) -> bool;
+
+    /// whether the total number of records can be selected along with a page of them
+    /// using the window function `COUNT(*) OVER()`
+    fn supports_window_functions(&self) -> bool { false }
+
+    /// whether the records of a query can be fetched in chunks from a server side cursor
+    /// declared with `DECLARE ... CURSOR FOR`
+    fn supports_cursors(&self) -> bool { false }
+
+    /// the function which returns the auto generated key of the last inserted record
+    /// on the connection, used to retrieve the record when there is no `RETURNING`
+    fn last_insert_id(&self) -> Option<&'static str> { None }
+
+    /// the pseudo column of the row id which is returned by `last_insert_id`, used to retrieve
+    /// the inserted records whose key is generated or of a table that has no primary key
+    fn row_id(&self) -> Option<&'static str> { None }
+
+    /// the difference between the successive keys generated in a multi row insert, when it
+    /// can be set in the database
+    fn auto_increment_step(&self) -> Option<&'static str> { None }
+
+    /// the number of bound params that can be in a statement, the bulk inserts are split
+    /// into chunks below it. This is 999 in the sqlite builds older than 3.32, the limit
+    /// of the sqlite connection is used instead with `DBPlatform::max_params`
+    fn max_params(&self) -> usize { 999 }
+
+    /// the number of records that can be inserted in a statement
+    fn max_insert_rows(&self) -> usize { usize::MAX }
+
+    /// the `INSERT` which skips the records that conflict with an existing one, used instead
+    /// of the `on_conflict` clause for `UpsertAction::DoNothing`
+    fn insert_ignore(&self) -> Option<&'static str> { None }
+
+    /// the clause added to an `INSERT` which does the action when the inserted record
+    /// conflicts with an existing one on the unique `conflict_columns`
+    fn on_conflict(&self, conflict_columns: &[&str], action: &UpsertAction) -> String {
+        let target = conflict_columns
+            .iter()
+            .map(|column| self.safe_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match action {
+            UpsertAction::Update(columns) if !columns.is_empty() => {
+                let assignments = columns
+                    .iter()
+                    .map(|column| {
+                        let column = self.safe_identifier(column);
+                        format!("{} = EXCLUDED.{}", column, column)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("\nON CONFLICT ({}) DO UPDATE SET {}", target, assignments)
+            }
+            _ => format!("\nON CONFLICT ({}) DO NOTHING", target),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote_only_when_needed() {
+        let pg = PostgresDialect;
+        assert_eq!(pg.safe_identifier("actor"), "actor");
+        assert_eq!(pg.safe_identifier("user"), "\"user\"");
+        assert_eq!(pg.safe_identifier("Actor"), "\"Actor\"");
+        assert_eq!(pg.safe_identifier("first name"), "\"first name\"");
+        assert_eq!(pg.safe_identifier("a\"b"), "\"a\"\"b\"");
+        assert_eq!(pg.safe_identifier("1st"), "\"1st\"");
+
+        let my = MysqlDialect;
+        assert_eq!(my.safe_identifier("Actor"), "Actor");
+        assert_eq!(my.safe_identifier("order"), "`order`");
+        assert_eq!(my.safe_identifier("a`b"), "`a``b`");
+
+        let sq = SqliteDialect;
+        assert_eq!(sq.safe_identifier("user"), "user");
+        assert_eq!(sq.safe_identifier("group"), "\"group\"");
+    }
+
+    #[test]
+    fn qualified_names() {
+        let pg = PostgresDialect;
+        assert_eq!(
+            pg.table_name(&TableName::from("public.user")),
+            "public.\"user\""
+        );
+        assert_eq!(pg.column_name(&ColumnName::from("user.order")), "\"user\".\"order\"");
+        let my = MysqlDialect;
+        assert_eq!(my.table_name(&TableName::from("sakila.actor")), "sakila.actor");
+    }
+
+    #[test]
+    fn placeholders_and_limits() {
+        assert_eq!(PostgresDialect.placeholder(3), "$3");
+        assert_eq!(SqliteDialect.placeholder(3), "?3");
+        assert_eq!(MysqlDialect.placeholder(3), "?");
+
+        assert_eq!(PostgresDialect.limit_offset(None, Some(5)), "OFFSET 5");
+        assert_eq!(SqliteDialect.limit_offset(None, Some(5)), "LIMIT -1 OFFSET 5");
+        assert_eq!(
+            MysqlDialect.limit_offset(None, Some(5)),
+            "LIMIT 18446744073709551615 OFFSET 5"
+        );
+        assert_eq!(SqliteDialect.limit_offset(Some(10), None), "LIMIT 10");
+    }
+
+    #[test]
+    fn upsert_clauses() {
+        let update = UpsertAction::update(&["name", "order"]);
+        assert_eq!(
+            PostgresDialect.on_conflict(&["id"], &update),
+            "\nON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, \"order\" = EXCLUDED.\"order\""
+        );
+        assert_eq!(
+            SqliteDialect.on_conflict(&["id", "code"], &UpsertAction::DoNothing),
+            "\nON CONFLICT (id, code) DO NOTHING"
+        );
+        assert_eq!(
+            MysqlDialect.on_conflict(&["id"], &update),
+            "\nON DUPLICATE KEY UPDATE name = VALUES(name), `order` = VALUES(`order`)"
+        );
+        assert_eq!(
+            MysqlDialect.on_conflict(&["id"], &UpsertAction::update(&[])),
+            "\nON DUPLICATE KEY UPDATE id = id"
+        );
+        assert_eq!(MysqlDialect.insert_ignore(), Some("INSERT IGNORE"));
+        assert_eq!(SqliteDialect.insert_ignore(), None);
+    }
+}
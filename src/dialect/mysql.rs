@@ -0,0 +1,106 @@
+use super::Dialect;
+use crate::UpsertAction;
+
+pub struct MysqlDialect;
+
+/// the reserved words of mysql 8.0
+#[rustfmt::skip]
+const RESERVED_WORDS: &[&str] = &[
+    "ACCESSIBLE", "ADD", "ALL", "ALTER", "ANALYZE", "AND", "AS", "ASC", "ASENSITIVE", "BEFORE",
+    "BETWEEN", "BIGINT", "BINARY", "BLOB", "BOTH", "BY", "CALL", "CASCADE", "CASE", "CHANGE",
+    "CHAR", "CHARACTER", "CHECK", "COLLATE", "COLUMN", "CONDITION", "CONSTRAINT", "CONTINUE",
+    "CONVERT", "CREATE", "CROSS", "CUBE", "CUME_DIST", "CURRENT_DATE", "CURRENT_TIME",
+    "CURRENT_TIMESTAMP", "CURRENT_USER", "CURSOR", "DATABASE", "DATABASES", "DAY_HOUR",
+    "DAY_MICROSECOND", "DAY_MINUTE", "DAY_SECOND", "DEC", "DECIMAL", "DECLARE", "DEFAULT",
+    "DELAYED", "DELETE", "DENSE_RANK", "DESC", "DESCRIBE", "DETERMINISTIC", "DISTINCT",
+    "DISTINCTROW", "DIV", "DOUBLE", "DROP", "DUAL", "EACH", "ELSE", "ELSEIF", "EMPTY",
+    "ENCLOSED", "ESCAPED", "EXCEPT", "EXISTS", "EXIT", "EXPLAIN", "FALSE", "FETCH",
+    "FIRST_VALUE", "FLOAT", "FLOAT4", "FLOAT8", "FOR", "FORCE", "FOREIGN", "FROM", "FULLTEXT",
+    "FUNCTION", "GENERATED", "GET", "GRANT", "GROUP", "GROUPING", "GROUPS", "HAVING",
+    "HIGH_PRIORITY", "HOUR_MICROSECOND", "HOUR_MINUTE", "HOUR_SECOND", "IF", "IGNORE", "IN",
+    "INDEX", "INFILE", "INNER", "INOUT", "INSENSITIVE", "INSERT", "INT", "INT1", "INT2", "INT3",
+    "INT4", "INT8", "INTEGER", "INTERSECT", "INTERVAL", "INTO", "IO_AFTER_GTIDS",
+    "IO_BEFORE_GTIDS", "IS", "ITERATE", "JOIN", "JSON_TABLE", "KEY", "KEYS", "KILL", "LAG",
+    "LAST_VALUE", "LATERAL", "LEAD", "LEADING", "LEAVE", "LEFT", "LIKE", "LIMIT", "LINEAR",
+    "LINES", "LOAD", "LOCALTIME", "LOCALTIMESTAMP", "LOCK", "LONG", "LONGBLOB", "LONGTEXT",
+    "LOOP", "LOW_PRIORITY", "MASTER_BIND", "MASTER_SSL_VERIFY_SERVER_CERT", "MATCH",
+    "MAXVALUE", "MEDIUMBLOB", "MEDIUMINT", "MEDIUMTEXT", "MIDDLEINT", "MINUTE_MICROSECOND",
+    "MINUTE_SECOND", "MOD", "MODIFIES", "NATURAL", "NOT", "NO_WRITE_TO_BINLOG", "NTH_VALUE",
+    "NTILE", "NULL", "NUMERIC", "OF", "ON", "OPTIMIZE", "OPTIMIZER_COSTS", "OPTION",
+    "OPTIONALLY", "OR", "ORDER", "OUT", "OUTER", "OUTFILE", "OVER", "PARTITION",
+    "PERCENT_RANK", "PRECISION", "PRIMARY", "PROCEDURE", "PURGE", "RANGE", "RANK", "READ",
+    "READS", "READ_WRITE", "REAL", "RECURSIVE", "REFERENCES", "REGEXP", "RELEASE", "RENAME",
+    "REPEAT", "REPLACE", "REQUIRE", "RESIGNAL", "RESTRICT", "RETURN", "REVOKE", "RIGHT",
+    "RLIKE", "ROW", "ROWS", "ROW_NUMBER", "SCHEMA", "SCHEMAS", "SECOND_MICROSECOND", "SELECT",
+    "SENSITIVE", "SEPARATOR", "SET", "SHOW", "SIGNAL", "SMALLINT", "SPATIAL", "SPECIFIC", "SQL",
+    "SQLEXCEPTION", "SQLSTATE", "SQLWARNING", "SQL_BIG_RESULT", "SQL_CALC_FOUND_ROWS",
+    "SQL_SMALL_RESULT", "SSL", "STARTING", "STORED", "STRAIGHT_JOIN", "SYSTEM", "TABLE",
+    "TERMINATED", "THEN", "TINYBLOB", "TINYINT", "TINYTEXT", "TO", "TRAILING", "TRIGGER",
+    "TRUE", "UNDO", "UNION", "UNIQUE", "UNLOCK", "UNSIGNED", "UPDATE", "USAGE", "USE", "USING",
+    "UTC_DATE", "UTC_TIME", "UTC_TIMESTAMP", "VALUES", "VARBINARY", "VARCHAR", "VARCHARACTER",
+    "VARYING", "VIRTUAL", "WHEN", "WHERE", "WHILE", "WINDOW", "WITH", "WRITE", "XOR",
+    "YEAR_MONTH", "ZEROFILL",
+];
+
+impl Dialect for MysqlDialect {
+    /// mysql params are positional `?`
+    fn placeholder(&self, _index: usize) -> String { "?".to_string() }
+
+    fn identifier_quote(&self) -> char { '`' }
+
+    fn reserved_words(&self) -> &'static [&'static str] { RESERVED_WORDS }
+
+    /// mysql can not have an `OFFSET` without a `LIMIT`,
+    /// the largest unsigned bigint is used to mean no limit
+    fn limit_offset(&self, limit: Option<usize>, offset: Option<usize>) -> String {
+        match (limit, offset) {
+            (None, Some(offset)) => format!("LIMIT 18446744073709551615 OFFSET {}", offset),
+            (Some(limit), Some(offset)) => format!("LIMIT {} OFFSET {}", limit, offset),
+            (Some(limit), None) => format!("LIMIT {}", limit),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn supports_returning(&self) -> bool { false }
+
+    fn last_insert_id(&self) -> Option<&'static str> { Some("LAST_INSERT_ID()") }
+
+    fn auto_increment_step(&self) -> Option<&'static str> { Some("@@auto_increment_increment") }
+
+    fn max_params(&self) -> usize { 65535 }
+
+    /// the statement has to fit in the `max_allowed_packet`, which is 4MB by default
+    /// in mysql 5.7
+    fn max_insert_rows(&self) -> usize { 1000 }
+
+    /// a conflicting record left unchanged by `ON DUPLICATE KEY UPDATE` is still counted as
+    /// affected with `CLIENT_FOUND_ROWS`, while it is not with `INSERT IGNORE`
+    fn insert_ignore(&self) -> Option<&'static str> { Some("INSERT IGNORE") }
+
+    /// mysql has no conflict target, the conflict is on any of the unique keys of the table.
+    /// Nothing is done by setting the first conflict column to itself
+    fn on_conflict(&self, conflict_columns: &[&str], action: &UpsertAction) -> String {
+        let assignments: Vec<String> = match action {
+            UpsertAction::Update(columns) if !columns.is_empty() => {
+                columns
+                    .iter()
+                    .map(|column| {
+                        let column = self.safe_identifier(column);
+                        format!("{} = VALUES({})", column, column)
+                    })
+                    .collect()
+            }
+            _ => {
+                conflict_columns
+                    .iter()
+                    .take(1)
+                    .map(|column| {
+                        let column = self.safe_identifier(column);
+                        format!("{} = {}", column, column)
+                    })
+                    .collect()
+            }
+        };
+        format!("\nON DUPLICATE KEY UPDATE {}", assignments.join(", "))
+    }
+}
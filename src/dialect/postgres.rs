@@ -0,0 +1,52 @@
+use super::Dialect;
+
+pub struct PostgresDialect;
+
+/// the keywords which are reserved in postgresql, including the ones that
+/// can still be used as a function or type name
+#[rustfmt::skip]
+const RESERVED_WORDS: &[&str] = &[
+    "ALL", "ANALYSE", "ANALYZE", "AND", "ANY", "ARRAY", "AS", "ASC", "ASYMMETRIC",
+    "AUTHORIZATION", "BINARY", "BOTH", "CASE", "CAST", "CHECK", "COLLATE", "COLLATION",
+    "COLUMN", "CONCURRENTLY", "CONSTRAINT", "CREATE", "CROSS", "CURRENT_CATALOG",
+    "CURRENT_DATE", "CURRENT_ROLE", "CURRENT_SCHEMA", "CURRENT_TIME", "CURRENT_TIMESTAMP",
+    "CURRENT_USER", "DEFAULT", "DEFERRABLE", "DESC", "DISTINCT", "DO", "ELSE", "END",
+    "EXCEPT", "FALSE", "FETCH", "FOR", "FOREIGN", "FREEZE", "FROM", "FULL", "GRANT",
+    "GROUP", "HAVING", "ILIKE", "IN", "INITIALLY", "INNER", "INTERSECT", "INTO", "IS",
+    "ISNULL", "JOIN", "LATERAL", "LEADING", "LEFT", "LIKE", "LIMIT", "LOCALTIME",
+    "LOCALTIMESTAMP", "NATURAL", "NOT", "NOTNULL", "NULL", "OFFSET", "ON", "ONLY", "OR",
+    "ORDER", "OUTER", "OVERLAPS", "PLACING", "PRIMARY", "REFERENCES", "RETURNING", "RIGHT",
+    "SELECT", "SESSION_USER", "SIMILAR", "SOME", "SYMMETRIC", "SYSTEM_USER", "TABLE",
+    "TABLESAMPLE", "THEN", "TO", "TRAILING", "TRUE", "UNION", "UNIQUE", "USER", "USING",
+    "VARIADIC", "VERBOSE", "WHEN", "WHERE", "WINDOW", "WITH",
+];
+
+impl Dialect for PostgresDialect {
+    fn placeholder(&self, index: usize) -> String { format!("${}", index) }
+
+    fn reserved_words(&self) -> &'static [&'static str] { RESERVED_WORDS }
+
+    /// unquoted identifiers are folded to lowercase in postgresql,
+    /// so the ones with uppercase letters has to be quoted
+    fn is_plain_identifier(&self, ident: &str) -> bool {
+        let mut chars = ident.chars();
+        match chars.next() {
+            Some(first) if first.is_ascii_lowercase() || first == '_' => {
+                chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                    && !self.is_reserved(ident)
+            }
+            _ => false,
+        }
+    }
+
+    fn supports_array_params(&self) -> bool { true }
+
+    fn supports_returning(&self) -> bool { true }
+
+    fn supports_window_functions(&self) -> bool { true }
+
+    fn supports_cursors(&self) -> bool { true }
+
+    /// the number of params is sent as a 16 bit integer
+    fn max_params(&self) -> usize { 65535 }
+}
@@ -0,0 +1,61 @@
+use super::Dialect;
+
+pub struct SqliteDialect;
+
+/// the keywords of sqlite
+#[rustfmt::skip]
+const RESERVED_WORDS: &[&str] = &[
+    "ABORT", "ACTION", "ADD", "AFTER", "ALL", "ALTER", "ALWAYS", "ANALYZE", "AND", "AS", "ASC",
+    "ATTACH", "AUTOINCREMENT", "BEFORE", "BEGIN", "BETWEEN", "BY", "CASCADE", "CASE", "CAST",
+    "CHECK", "COLLATE", "COLUMN", "COMMIT", "CONFLICT", "CONSTRAINT", "CREATE", "CROSS",
+    "CURRENT", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "DATABASE", "DEFAULT",
+    "DEFERRABLE", "DEFERRED", "DELETE", "DESC", "DETACH", "DISTINCT", "DO", "DROP", "EACH",
+    "ELSE", "END", "ESCAPE", "EXCEPT", "EXCLUDE", "EXCLUSIVE", "EXISTS", "EXPLAIN", "FAIL",
+    "FILTER", "FIRST", "FOLLOWING", "FOR", "FOREIGN", "FROM", "FULL", "GENERATED", "GLOB",
+    "GROUP", "GROUPS", "HAVING", "IF", "IGNORE", "IMMEDIATE", "IN", "INDEX", "INDEXED",
+    "INITIALLY", "INNER", "INSERT", "INSTEAD", "INTERSECT", "INTO", "IS", "ISNULL", "JOIN",
+    "KEY", "LAST", "LEFT", "LIKE", "LIMIT", "MATCH", "MATERIALIZED", "NATURAL", "NO", "NOT",
+    "NOTHING", "NOTNULL", "NULL", "NULLS", "OF", "OFFSET", "ON", "OR", "ORDER", "OTHERS",
+    "OUTER", "OVER", "PARTITION", "PLAN", "PRAGMA", "PRECEDING", "PRIMARY", "QUERY", "RAISE",
+    "RANGE", "RECURSIVE", "REFERENCES", "REGEXP", "REINDEX", "RELEASE", "RENAME", "REPLACE",
+    "RESTRICT", "RETURNING", "RIGHT", "ROLLBACK", "ROW", "ROWS", "SAVEPOINT", "SELECT", "SET",
+    "TABLE", "TEMP", "TEMPORARY", "THEN", "TIES", "TO", "TRANSACTION", "TRIGGER", "UNBOUNDED",
+    "UNION", "UNIQUE", "UPDATE", "USING", "VACUUM", "VALUES", "VIEW", "VIRTUAL", "WHEN",
+    "WHERE", "WINDOW", "WITH", "WITHOUT",
+];
+
+impl Dialect for SqliteDialect {
+    /// sqlite numbered param `?NNN`, the same index can be referred more than once
+    fn placeholder(&self, index: usize) -> String { format!("?{}", index) }
+
+    fn reserved_words(&self) -> &'static [&'static str] { RESERVED_WORDS }
+
+    /// boolean is stored as integer in sqlite
+    fn bool_literal(&self, value: bool) -> &'static str {
+        if value {
+            "1"
+        } else {
+            "0"
+        }
+    }
+
+    /// sqlite can not have an `OFFSET` without a `LIMIT`, a negative limit means no limit
+    fn limit_offset(&self, limit: Option<usize>, offset: Option<usize>) -> String {
+        match (limit, offset) {
+            (None, Some(offset)) => format!("LIMIT -1 OFFSET {}", offset),
+            (Some(limit), Some(offset)) => format!("LIMIT {} OFFSET {}", limit, offset),
+            (Some(limit), None) => format!("LIMIT {}", limit),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn supports_returning(&self) -> bool { false }
+
+    fn last_insert_id(&self) -> Option<&'static str> { Some("last_insert_rowid()") }
+
+    fn row_id(&self) -> Option<&'static str> { Some("ROWID") }
+
+    /// the inserted records are retrieved with an `OR` of their composite keys, which is
+    /// below the maximum depth of an expression, 1000 by default
+    fn max_insert_rows(&self) -> usize { 500 }
+}
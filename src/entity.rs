@@ -4,25 +4,51 @@ use crate::db_auth::{
     User,
 };
 use crate::{
-    table::SchemaContent,
+    mutation,
+    select::TOTAL_COLUMN,
+    statement::Statement,
+    table::{
+        SchemaContent,
+        TableKey,
+    },
+    Array,
+    Cursor,
     DBPlatform,
     DataError,
     Database,
     DatabaseName,
     DbError,
+    Direction,
+    Expr,
+    Filter,
+    Operator,
+    Page,
     Rows,
+    RetryPolicy,
+    Select,
     TableDef,
     ToValue,
+    Transaction,
+    TransactionOptions,
+    UpsertAction,
     Value,
 };
 
 use rustorm_dao::{
-    FromDao,
+    ColumnName,
+    Dao,
+    DaoError,
+    FromValue,
     TableName,
     ToColumnNames,
     ToDao,
+    ToEnumVariants,
+    ToKeyValues,
+    ToPrimaryKey,
     ToTableName,
+    TryFromDao,
 };
+use std::thread;
 
 pub struct EntityManager(pub DBPlatform);
 
@@ -33,8 +59,81 @@ impl EntityManager {
 
     pub fn rollback_transaction(&mut self) -> Result<(), DbError> { self.0.rollback_transaction() }
 
+    /// the number of transactions that are open, the nested ones are savepoints
+    pub fn transaction_depth(&self) -> usize { self.0.transaction_depth() }
+
+    /// begin a transaction, which is rolled back when the returned guard is dropped
+    /// without being committed. A transaction begun inside another one is a savepoint.
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, DbError> {
+        Transaction::new(self, &TransactionOptions::default())
+    }
+
+    /// begin a transaction with the isolation level and access mode of the options
+    pub fn transaction_with_options(
+        &mut self,
+        options: &TransactionOptions,
+    ) -> Result<Transaction<'_>, DbError> {
+        Transaction::new(self, options)
+    }
+
+    /// run the closure in a transaction which is committed when the closure returns `Ok`.
+    /// The whole transaction is run again when it fails with an error that is safe to retry,
+    /// such as a serialization failure or a deadlock, so the closure may be called more than
+    /// once.
+    ///
+    /// ```rust,ignore
+    /// let count = em.run_in_transaction(|tx| {
+    ///     tx.update(&actor)?;
+    ///     tx.delete(&old_actor)
+    /// })?;
+    /// ```
+    pub fn run_in_transaction<F, R>(&mut self, f: F) -> Result<R, DbError>
+    where
+        F: FnMut(&mut Transaction<'_>) -> Result<R, DbError>,
+    {
+        self.run_in_transaction_with(&TransactionOptions::default(), &RetryPolicy::default(), f)
+    }
+
+    /// run the closure in a transaction with the options, retrying it according to the policy.
+    /// A transaction nested in another one is never retried, since the error aborts
+    /// the outer transaction as well.
+    pub fn run_in_transaction_with<F, R>(
+        &mut self,
+        options: &TransactionOptions,
+        policy: &RetryPolicy,
+        mut f: F,
+    ) -> Result<R, DbError>
+    where
+        F: FnMut(&mut Transaction<'_>) -> Result<R, DbError>,
+    {
+        let nested = self.transaction_depth() > 0;
+        let mut attempt = 0;
+        loop {
+            let result = self.transaction_with_options(options).and_then(|mut tx| {
+                let ret = f(&mut tx)?;
+                tx.commit()?;
+                Ok(ret)
+            });
+            match result {
+                Err(e) if !nested && attempt < policy.max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    let backoff = policy.backoff(attempt);
+                    warn!(
+                        "Retrying the transaction in {:?}, attempt {}: {}",
+                        backoff, attempt, e
+                    );
+                    thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+
     pub fn set_session_user(&mut self, username: &str) -> Result<(), DbError> {
-        let sql = format!("SET SESSION ROLE '{}'", username);
+        let sql = format!(
+            "SET SESSION ROLE {}",
+            self.0.dialect().quote_identifier(username)
+        );
         self.0.execute_sql_with_return(&sql, &[])?;
         Ok(())
     }
@@ -66,27 +165,163 @@ impl EntityManager {
     /// get all the records of this table
     pub fn get_all<T>(&mut self) -> Result<Vec<T>, DbError>
     where
-        T: ToTableName + ToColumnNames + FromDao,
+        T: ToTableName + ToColumnNames + TryFromDao,
     {
-        let table = T::to_table_name();
-        let columns = T::to_column_names();
-        let enumerated_columns = columns
+        self.select(&Select::<T>::new())
+    }
+
+    /// get the records matching the select query
+    pub fn select<T>(&mut self, select: &Select<T>) -> Result<Vec<T>, DbError>
+    where
+        T: TryFromDao,
+    {
+        select.check_cursor()?;
+        let statement = select.build(self.0.dialect());
+        let rows = self
+            .0
+            .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+        Ok(rows
             .iter()
-            .map(|c| c.name.to_owned())
-            .collect::<Vec<_>>()
-            .join(", ");
-        let sql = format!(
-            "SELECT {} FROM {}",
-            enumerated_columns,
-            table.complete_name()
-        );
-        let rows = self.0.execute_sql_with_return(&sql, &[])?;
-        let mut entities = vec![];
-        for dao in rows.iter() {
-            let entity = T::from_dao(&dao);
-            entities.push(entity)
+            .map(|dao| T::try_from_dao(&dao))
+            .collect::<Result<Vec<T>, _>>()?)
+    }
+
+    /// retrieve a page of the records of the query using an offset, the first page is 1.
+    /// The limit and offset of the query are replaced. The total number of records is
+    /// selected along with the page in platforms that has window functions, otherwise it
+    /// is counted in a separate query unless this is the last page
+    pub fn paginate<T>(
+        &mut self,
+        select: &Select<T>,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Page<T>, DbError>
+    where
+        T: TryFromDao,
+    {
+        check_per_page(per_page)?;
+        select.check_cursor()?;
+        let offset = page
+            .checked_sub(1)
+            .and_then(|previous| previous.checked_mul(per_page))
+            .ok_or(DataError::PageOutOfRange(page))?;
+        let query = select.clone().limit(per_page).offset(offset);
+        let dialect = self.0.dialect();
+        let mut rows = if dialect.supports_window_functions() {
+            let statement = query.with_total().build(dialect);
+            let mut rows = self
+                .0
+                .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+            rows.count = take_total(&mut rows)?;
+            rows
+        } else {
+            let statement = query.build(dialect);
+            self.0
+                .execute_sql_with_return(&statement.sql, &statement.param_refs())?
+        };
+        let len = rows.data.len();
+        let total = match rows.count {
+            Some(total) => total,
+            // the last page has less records than a full page
+            None if (len > 0 && len < per_page) || (len == 0 && offset == 0) => offset + len,
+            None => self.count(select)?,
+        };
+        rows.count = Some(total);
+        to_page(select, rows, per_page, offset + len < total)
+    }
+
+    /// retrieve a page of the records that come after the `Select::after` cursor of the
+    /// query, which is the `next_cursor` of the previous page. The total is not counted
+    pub fn paginate_after<T>(
+        &mut self,
+        select: &Select<T>,
+        per_page: usize,
+    ) -> Result<Page<T>, DbError>
+    where
+        T: TryFromDao,
+    {
+        check_per_page(per_page)?;
+        select.check_cursor()?;
+        // one more record is retrieved to know if there is a next page
+        let statement = select
+            .clone()
+            .limit(per_page.saturating_add(1))
+            .build(self.0.dialect());
+        let mut rows = self
+            .0
+            .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+        let has_next = rows.data.len() > per_page;
+        rows.data.truncate(per_page);
+        to_page(select, rows, per_page, has_next)
+    }
+
+    /// the number of records matched by the query regardless of its limit and offset
+    fn count<T>(&mut self, select: &Select<T>) -> Result<usize, DbError> {
+        let statement = select.build_count(self.0.dialect());
+        let rows = self
+            .0
+            .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+        let dao = rows.iter().next().ok_or(DataError::ZeroRecordReturned)?;
+        Ok(dao.get::<i64>("count")? as usize)
+    }
+
+    /// get the record that has this primary key value, the key declared in the entity is used
+    /// before the one of the table. The values of a composite key are passed as a tuple,
+    /// in the order of the columns of the primary key:
+    /// `em.find_by_id::<FilmActor, _>((actor_id, film_id))`
+    pub fn find_by_id<T, K>(&mut self, key: K) -> Result<Option<T>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToPrimaryKey + TryFromDao,
+        K: ToKeyValues,
+    {
+        let mut records: Vec<T> = self.find_by_ids(&[key])?;
+        match records.len() {
+            0 => Ok(None),
+            1 => Ok(Some(records.remove(0))),
+            _ => Err(DbError::DataError(DataError::MoreThan1RecordReturned)),
         }
-        Ok(entities)
+    }
+
+    /// get the records that has any of these primary key values, in a single query.
+    /// The records are not necessarily in the same order as the keys.
+    pub fn find_by_ids<T, K>(&mut self, keys: &[K]) -> Result<Vec<T>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToPrimaryKey + TryFromDao,
+        K: ToKeyValues,
+    {
+        let primary_columns = self.get_entity_primary_column_names::<T>()?;
+        let mut key_values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let values = key.to_key_values();
+            if values.len() != primary_columns.len() {
+                return Err(DataError::PrimaryKeyMismatch {
+                    expected: primary_columns.len(),
+                    found: values.len(),
+                }
+                .into());
+            }
+            key_values.push(values);
+        }
+        let filter = match primary_columns.as_slice() {
+            [column] => {
+                let values: Vec<Value> = key_values.into_iter().flatten().collect();
+                match to_array(&values) {
+                    Some(array) if self.0.dialect().supports_array_params() => {
+                        Filter::any(column.as_str(), array)
+                    }
+                    _ => Filter::In(Expr::column(column), values),
+                }
+            }
+            _ => {
+                Filter::Or(
+                    key_values
+                        .into_iter()
+                        .map(|values| Filter::primary_key(&primary_columns, values))
+                        .collect(),
+                )
+            }
+        };
+        self.select(&Select::<T>::new().filter(filter))
     }
 
     /// get the table from database based on this column name
@@ -94,6 +329,23 @@ impl EntityManager {
         self.0.get_table(table_name)
     }
 
+    /// check that the variants of the rust enum are the same as the choices of the enum column
+    /// of the table, so that every value can be converted both ways
+    pub fn check_enum<E>(&mut self, table_name: &TableName, column: &str) -> Result<(), DbError>
+    where
+        E: ToEnumVariants,
+    {
+        let table = self
+            .get_table(table_name)?
+            .ok_or_else(|| DataError::TableNameNotFound(table_name.complete_name()))?;
+        let column_def = table
+            .columns
+            .iter()
+            .find(|column_def| column_def.name.name == column)
+            .ok_or_else(|| DataError::ColumnNameNotFound(column.to_string()))?;
+        Ok(column_def.check_enum::<E>()?)
+    }
+
     /// set the autoincrement value of the primary column(if present) of this table.
     /// If the primary column of this table is not an autoincrement, returns Ok(None).
     pub fn set_autoincrement_value(
@@ -128,7 +380,7 @@ impl EntityManager {
         }
         let sql = format!(
             "SELECT COUNT(*) AS count FROM {}",
-            table_name.complete_name()
+            self.0.dialect().table_name(table_name)
         );
         let count: Result<Count, DbError> = self.execute_sql_with_one_return(&sql, &[]);
         count.map(|c| c.count as usize)
@@ -143,19 +395,17 @@ impl EntityManager {
         self.0.get_grouped_tables()
     }
 
-    #[allow(unused_variables)]
+    /// insert the entities and retrieve the inserted records. The generated and read only
+    /// columns are left out of the insert, so their values are the ones set by the database.
     pub fn insert<T, R>(&mut self, entities: &[&T]) -> Result<Vec<R>, DbError>
     where
-        T: ToTableName + ToColumnNames + ToDao,
-        R: FromDao + ToColumnNames,
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
     {
-        match self.0 {
-            #[cfg(feature = "with-sqlite")]
-            DBPlatform::Sqlite(_) => self.insert_simple(entities),
-            #[cfg(feature = "with-postgres")]
-            DBPlatform::Postgres(_) => self.insert_bulk_with_returning_support(entities),
-            #[cfg(feature = "with-mysql")]
-            DBPlatform::Mysql(_) => self.insert_simple(entities),
+        if self.0.dialect().supports_returning() {
+            self.insert_bulk_with_returning_support(entities)
+        } else {
+            self.in_chunks(entities, Self::insert_rows)
         }
     }
 
@@ -165,30 +415,60 @@ impl EntityManager {
         entities: &[&T],
     ) -> Result<Vec<R>, DbError>
     where
-        T: ToTableName + ToColumnNames + ToDao,
-        R: FromDao + ToColumnNames,
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
+    {
+        self.in_chunks(entities, Self::insert_rows_returning)
+    }
+
+    /// insert the entities in chunks that are below the number of params and records a
+    /// statement can have in the platform, the chunks are inserted in one transaction
+    fn in_chunks<T, R, F>(&mut self, entities: &[&T], insert: F) -> Result<Vec<R>, DbError>
+    where
+        T: ToColumnNames + ToPrimaryKey,
+        F: Fn(&mut EntityManager, &[&T]) -> Result<Vec<R>, DbError>,
+    {
+        if entities.is_empty() {
+            return Ok(vec![]);
+        }
+        let dialect = self.0.dialect();
+        let columns = insert_columns::<T>().len().max(1);
+        let chunk_size = (self.0.max_params() / columns)
+            .min(dialect.max_insert_rows())
+            .max(1);
+        if entities.len() <= chunk_size {
+            return insert(self, entities);
+        }
+        let mut tx = self.transaction()?;
+        let mut retrieved_entities = Vec::with_capacity(entities.len());
+        for chunk in entities.chunks(chunk_size) {
+            retrieved_entities.extend(insert(&mut tx, chunk)?);
+        }
+        tx.commit()?;
+        Ok(retrieved_entities)
+    }
+
+    /// insert the entities in a single statement and retrieve them with `RETURNING`
+    fn insert_rows_returning<T, R>(&mut self, entities: &[&T]) -> Result<Vec<R>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
     {
-        let columns = T::to_column_names();
-        let mut sql = self.build_insert_clause(entities);
+        let columns = insert_columns::<T>();
+        let mut sql =
+            self.build_insert_clause("INSERT", &T::to_table_name(), &columns, entities.len());
         let return_columns = R::to_column_names();
         sql += &self.build_returning_clause(return_columns);
 
         let mut values: Vec<Value> = Vec::with_capacity(entities.len() * columns.len());
         for entity in entities {
-            let dao = entity.to_dao();
-            for col in columns.iter() {
-                let value = dao.get_value(&col.name);
-                match value {
-                    Some(value) => values.push(value.clone()),
-                    None => values.push(Value::Nil),
-                }
-            }
+            values.extend(column_values(&columns, &entity.to_dao()));
         }
         let bvalues: Vec<&Value> = values.iter().collect();
         let rows = self.0.execute_sql_with_return(&sql, &bvalues)?;
         let mut retrieved_entities = vec![];
         for dao in rows.iter() {
-            let retrieved = R::from_dao(&dao);
+            let retrieved = R::try_from_dao(&dao)?;
             retrieved_entities.push(retrieved);
         }
         Ok(retrieved_entities)
@@ -198,111 +478,506 @@ impl EntityManager {
     /// insert such as sqlite
     pub fn single_insert<T>(&mut self, entity: &T) -> Result<(), DbError>
     where
-        T: ToTableName + ToColumnNames + ToDao,
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
     {
-        let columns = T::to_column_names();
-        let sql = self.build_insert_clause(&[entity]);
-        let dao = entity.to_dao();
-        let mut values: Vec<Value> = Vec::with_capacity(columns.len());
-        for col in columns.iter() {
-            let value = dao.get_value(&col.name);
-            match value {
-                Some(value) => values.push(value.clone()),
-                None => values.push(Value::Nil),
-            }
-        }
+        let columns = insert_columns::<T>();
+        self.insert_dao(&T::to_table_name(), &columns, &entity.to_dao())
+    }
+
+    fn insert_dao(
+        &mut self,
+        table: &TableName,
+        columns: &[ColumnName],
+        dao: &Dao,
+    ) -> Result<(), DbError> {
+        let sql = self.build_insert_clause("INSERT", table, columns, 1);
+        let values = column_values(columns, dao);
         let bvalues: Vec<&Value> = values.iter().collect();
-        self.0.execute_sql_with_return(&sql, &bvalues)?;
+        self.0.execute_sql(&sql, &bvalues)?;
         Ok(())
     }
 
-    /// this is soly for use with sqlite since sqlite doesn't support bulk insert
+    /// used in the platforms that have no `RETURNING` such as sqlite and mysql,
+    /// the entities are inserted one at a time and retrieved using their primary key,
+    /// or the row id when the key is generated or the table has no primary key
     pub fn insert_simple<T, R>(&mut self, entities: &[&T]) -> Result<Vec<R>, DbError>
     where
-        T: ToTableName + ToColumnNames + ToDao,
-        R: FromDao + ToColumnNames,
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
     {
-        let return_columns = R::to_column_names();
-        let return_column_names = return_columns
-            .iter()
-            .map(|rc| rc.name.to_owned())
-            .collect::<Vec<_>>()
-            .join(", ");
-
         let table = T::to_table_name();
-        //TODO: move this specific query to sqlite
-        let last_insert_sql = format!(
-            "\
-             SELECT {} \
-             FROM {} \
-             WHERE ROWID = (\
-             SELECT LAST_INSERT_ROWID() FROM {})",
-            return_column_names,
-            table.complete_name(),
-            table.complete_name()
-        );
+        let columns = insert_columns::<T>();
+        let key_columns = self.get_inserted_key_columns::<T>()?;
+        let row_id = self.0.dialect().row_id();
         let mut retrieved_entities = vec![];
-        println!("sql: {}", last_insert_sql);
         for entity in entities {
-            self.single_insert(*entity)?;
-            let retrieved = self.execute_sql_with_return(&last_insert_sql, &[])?;
-            retrieved_entities.extend(retrieved);
+            let dao = entity.to_dao();
+            self.insert_dao(&table, &columns, &dao)?;
+            let filter = match (inserted_key_values(&columns, &key_columns, &dao), row_id) {
+                (Some(key_values), _) => Filter::primary_key(&key_columns, key_values),
+                (None, Some(row_id)) => {
+                    Filter::Compare(Expr::column(row_id), Operator::Eq, self.get_last_insert_id()?)
+                }
+                (None, None) => {
+                    let key_values = self.get_inserted_key_values(&columns, &key_columns, &dao)?;
+                    Filter::primary_key(&key_columns, key_values)
+                }
+            };
+            let select = Select::<R>::from_table(table.clone()).filter(filter);
+            retrieved_entities.extend(self.select(&select)?);
         }
         Ok(retrieved_entities)
     }
 
+    /// insert the entities, or do the action on the existing records that conflict with them
+    /// on the `conflict_columns` which has a unique constraint. Returns the records that are
+    /// inserted or updated, the ones left unchanged by `UpsertAction::DoNothing` are not
+    /// returned. In mysql the conflict is on any of the unique keys of the table, and
+    /// `UpsertAction::DoNothing` uses `INSERT IGNORE` which also ignores the other errors
+    /// of the records such as a missing value of a not null column.
+    /// The conflict columns have to be inserted, they can not be generated or read only.
+    ///
+    /// ```rust,ignore
+    /// let actors: Vec<Actor> =
+    ///     em.upsert(&[&actor], &["actor_id"], UpsertAction::update(&["last_name"]))?;
+    /// ```
+    pub fn upsert<T, R>(
+        &mut self,
+        entities: &[&T],
+        conflict_columns: &[&str],
+        action: UpsertAction,
+    ) -> Result<Vec<R>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
+    {
+        if conflict_columns.is_empty() {
+            return Err(DbError::UnsupportedOperation(
+                "upsert without the columns of the conflict".to_string(),
+            ));
+        }
+        if entities.is_empty() {
+            return Ok(vec![]);
+        }
+        let table = T::to_table_name();
+        let columns = insert_columns::<T>();
+        // the records are retrieved with the inserted values of the conflict columns
+        if let Some(column) = conflict_columns
+            .iter()
+            .find(|column| !columns.iter().any(|c| c.name == **column))
+        {
+            return Err(DbError::UnsupportedOperation(format!(
+                "upsert with the conflict column `{}` which is not inserted",
+                column
+            )));
+        }
+        // updating no columns leaves the existing records unchanged in all the platforms
+        let action = match action {
+            UpsertAction::Update(columns) if columns.is_empty() => UpsertAction::DoNothing,
+            action => action,
+        };
+        let dialect = self.0.dialect();
+        let (insert, on_conflict) = match dialect.insert_ignore() {
+            Some(insert_ignore) if action == UpsertAction::DoNothing => {
+                (insert_ignore, String::new())
+            }
+            _ => ("INSERT", dialect.on_conflict(conflict_columns, &action)),
+        };
+        if dialect.supports_returning() {
+            let mut sql = self.build_insert_clause(insert, &table, &columns, entities.len());
+            sql += &on_conflict;
+            sql += &self.build_returning_clause(R::to_column_names());
+            let mut values: Vec<Value> = Vec::with_capacity(entities.len() * columns.len());
+            for entity in entities {
+                values.extend(column_values(&columns, &entity.to_dao()));
+            }
+            let bvalues: Vec<&Value> = values.iter().collect();
+            let rows = self.0.execute_sql_with_return(&sql, &bvalues)?;
+            Ok(rows
+                .iter()
+                .map(|dao| R::try_from_dao(&dao))
+                .collect::<Result<Vec<R>, _>>()?)
+        } else {
+            // the records are upserted one at a time and retrieved using the conflict columns
+            let sql = self.build_insert_clause(insert, &table, &columns, 1) + &on_conflict;
+            let conflict_columns: Vec<String> =
+                conflict_columns.iter().map(ToString::to_string).collect();
+            let mut retrieved_entities = vec![];
+            for entity in entities {
+                let dao = entity.to_dao();
+                let values = column_values(&columns, &dao);
+                let bvalues: Vec<&Value> = values.iter().collect();
+                let affected = self.0.execute_sql(&sql, &bvalues)?;
+                if affected == 0 && action == UpsertAction::DoNothing {
+                    continue;
+                }
+                let key_values = conflict_columns
+                    .iter()
+                    .map(|column| dao.get_value(column).cloned().unwrap_or(Value::Nil))
+                    .collect();
+                let select = Select::<R>::from_table(table.clone())
+                    .filter(Filter::primary_key(&conflict_columns, key_values));
+                retrieved_entities.extend(self.select(&select)?);
+            }
+            Ok(retrieved_entities)
+        }
+    }
+
+    /// insert the entities in a single statement in the platforms that have no `RETURNING`,
+    /// such as sqlite and mysql. The records are retrieved using their primary key when all
+    /// the keys are inserted, or using the range of keys generated by the statement when none
+    /// of them are. The entities that mix both are inserted one at a time with `insert_simple`
+    fn insert_rows<T, R>(&mut self, entities: &[&T]) -> Result<Vec<R>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
+    {
+        let table = T::to_table_name();
+        let columns = insert_columns::<T>();
+        let primary_columns = self.get_inserted_key_columns::<T>()?;
+        let daos: Vec<Dao> = entities.iter().map(|entity| entity.to_dao()).collect();
+        let inserted_keys: Option<Vec<Vec<Value>>> = daos
+            .iter()
+            .map(|dao| inserted_key_values(&columns, &primary_columns, dao))
+            .collect();
+        let all_generated = daos.iter().all(|dao| {
+            primary_columns
+                .iter()
+                .all(|column| is_generated(&columns, column, dao))
+        });
+        let generated_key_retrievable = self.0.dialect().row_id().is_some()
+            || (primary_columns.len() == 1 && self.0.dialect().last_insert_id().is_some());
+        if inserted_keys.is_none() && !(all_generated && generated_key_retrievable) {
+            return self.insert_simple(entities);
+        }
+
+        let sql = self.build_insert_clause("INSERT", &table, &columns, daos.len());
+        let mut values: Vec<Value> = Vec::with_capacity(daos.len() * columns.len());
+        for dao in daos.iter() {
+            values.extend(column_values(&columns, dao));
+        }
+        let bvalues: Vec<&Value> = values.iter().collect();
+        self.0.execute_sql(&sql, &bvalues)?;
+        let (primary_columns, keys) = match inserted_keys {
+            Some(keys) => (primary_columns, keys),
+            None => self.get_generated_keys(&primary_columns, daos.len())?,
+        };
+
+        let filter = match primary_columns.as_slice() {
+            [column] => Filter::In(Expr::column(column), keys.iter().flatten().cloned().collect()),
+            _ => {
+                Filter::Or(
+                    keys.iter()
+                        .map(|key| Filter::primary_key(&primary_columns, key.clone()))
+                        .collect(),
+                )
+            }
+        };
+        let mut select = Select::<R>::from_table(table).filter(filter);
+        for column in primary_columns.iter() {
+            select = select.order_by(column.as_str(), Direction::Asc);
+        }
+        let statement = select.build(self.0.dialect());
+        let rows = self
+            .0
+            .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+        let daos = in_key_order(rows.iter().collect(), &primary_columns, &keys);
+        Ok(daos
+            .iter()
+            .map(|dao| R::try_from_dao(dao))
+            .collect::<Result<Vec<R>, _>>()?)
+    }
+
+    /// the primary key values of the record that was just inserted from the dao.
+    /// The key column that is not inserted, or is inserted as null, is generated by the database
+    fn get_inserted_key_values(
+        &mut self,
+        columns: &[ColumnName],
+        primary_columns: &[String],
+        dao: &Dao,
+    ) -> Result<Vec<Value>, DbError> {
+        let mut values = Vec::with_capacity(primary_columns.len());
+        let mut generated = None;
+        for column in primary_columns {
+            let inserted = columns.iter().any(|c| &c.name == column);
+            match dao.get_value(column) {
+                Some(value) if inserted && *value != Value::Nil => values.push(value.clone()),
+                _ if generated.is_none() => {
+                    generated = Some(values.len());
+                    values.push(Value::Nil);
+                }
+                _ => {
+                    return Err(DbError::UnsupportedOperation(
+                        "only one generated primary key column can be retrieved after insert"
+                            .to_string(),
+                    ))
+                }
+            }
+        }
+        if let Some(index) = generated {
+            values[index] = self.get_last_insert_id()?;
+        }
+        Ok(values)
+    }
+
+    /// the keys generated by the last insert of `count` records, with the column they are in.
+    /// Sqlite gives the records of a statement the row ids that follow the largest one, the
+    /// last insert id is the row id of the last record. Mysql gives consecutive
+    /// `AUTO_INCREMENT` values to the records of an insert that has a known number of records,
+    /// spaced by the `auto_increment_increment`, the last insert id is the key of the first
+    /// record
+    fn get_generated_keys(
+        &mut self,
+        primary_columns: &[String],
+        count: usize,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), DbError> {
+        let last_insert_id = self.get_last_insert_id()?;
+        let last_insert_id = i64::from_value(&last_insert_id).map_err(DaoError::ConvertError)?;
+        let count = count as i64;
+        let dialect = self.0.dialect();
+        let (column, first, step) = match (dialect.row_id(), primary_columns) {
+            (Some(row_id), _) => (row_id.to_string(), last_insert_id - count + 1, 1),
+            (None, [column]) => {
+                let step = match dialect.auto_increment_step() {
+                    Some(step) => {
+                        let rows = self
+                            .0
+                            .execute_sql_with_return(&format!("SELECT {} AS step", step), &[])?;
+                        let dao = rows.iter().next().ok_or(DataError::ZeroRecordReturned)?;
+                        dao.get::<i64>("step")?
+                    }
+                    None => 1,
+                };
+                (column.clone(), last_insert_id, step)
+            }
+            (None, _) => {
+                return Err(DbError::UnsupportedOperation(
+                    "only one generated primary key column can be retrieved after insert"
+                        .to_string(),
+                ))
+            }
+        };
+        let keys = (0..count)
+            .map(|i| vec![Value::Bigint(first + i * step)])
+            .collect();
+        Ok((vec![column], keys))
+    }
+
+    /// the auto generated key of the last inserted record
+    fn get_last_insert_id(&mut self) -> Result<Value, DbError> {
+        let function = self.0.dialect().last_insert_id().ok_or_else(|| {
+            DbError::UnsupportedOperation(
+                "retrieving the generated key of the inserted record".to_string(),
+            )
+        })?;
+        let rows = self
+            .0
+            .execute_sql_with_return(&format!("SELECT {} AS id", function), &[])?;
+        rows.iter()
+            .next()
+            .and_then(|mut dao| dao.remove("id"))
+            .ok_or_else(|| DataError::ZeroRecordReturned.into())
+    }
+
+    /// update the record of this entity, the record is matched using the primary key
+    /// of the table. Returns the number of affected rows, which are the matched rows
+    /// including the ones that already have the same values.
+    pub fn update<T>(&mut self, entity: &T) -> Result<u64, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+    {
+        let statement = self.build_update_statement(entity)?;
+        self.0
+            .execute_sql(&statement.sql, &statement.param_refs())
+    }
+
+    /// update the record of this entity and retrieve the updated record.
+    /// This is only supported in platforms that has `RETURNING` such as postgresql
+    pub fn update_returning<T, R>(&mut self, entity: &T) -> Result<Option<R>, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+        R: TryFromDao + ToColumnNames,
+    {
+        if !self.0.dialect().supports_returning() {
+            return Err(DbError::UnsupportedOperation(
+                "update with returning is not supported in this platform".to_string(),
+            ));
+        }
+        let mut statement = self.build_update_statement(entity)?;
+        statement.sql += &self.build_returning_clause(R::to_column_names());
+        let rows = self
+            .0
+            .execute_sql_with_return(&statement.sql, &statement.param_refs())?;
+        match rows.iter().next() {
+            Some(dao) => Ok(Some(R::try_from_dao(&dao)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// delete the record of this entity, the record is matched using the primary key
+    /// of the entity or else of the table. Returns the number of affected rows.
+    pub fn delete<T>(&mut self, entity: &T) -> Result<u64, DbError>
+    where
+        T: ToTableName + ToDao + ToPrimaryKey,
+    {
+        let table = T::to_table_name();
+        let primary_columns = self.get_entity_primary_column_names::<T>()?;
+        let statement =
+            mutation::build_delete(self.0.dialect(), &table, &primary_columns, &entity.to_dao())?;
+        self.0
+            .execute_sql(&statement.sql, &statement.param_refs())
+    }
+
+    /// set the columns of all the records that match the filter to the values of the dao,
+    /// `UPDATE film SET rental_rate = $1 WHERE rating = $2`. Returns the number of affected rows,
+    /// which are the matched rows including the ones that already have the same values.
+    pub fn update_where<T>(&mut self, set: Dao, filter: Filter) -> Result<u64, DbError>
+    where
+        T: ToTableName,
+    {
+        let statement =
+            mutation::build_update_where(self.0.dialect(), &T::to_table_name(), &set, &filter)?;
+        self.0
+            .execute_sql(&statement.sql, &statement.param_refs())
+    }
+
+    /// delete all the records that match the filter,
+    /// `DELETE FROM rental WHERE return_date < $1`. Returns the number of affected rows.
+    pub fn delete_where<T>(&mut self, filter: Filter) -> Result<u64, DbError>
+    where
+        T: ToTableName,
+    {
+        let statement =
+            mutation::build_delete_where(self.0.dialect(), &T::to_table_name(), &filter);
+        self.0
+            .execute_sql(&statement.sql, &statement.param_refs())
+    }
+
+    /// the read only columns are left out of the update
+    fn build_update_statement<T>(&mut self, entity: &T) -> Result<Statement, DbError>
+    where
+        T: ToTableName + ToColumnNames + ToDao + ToPrimaryKey,
+    {
+        let table = T::to_table_name();
+        let primary_columns = self.get_entity_primary_column_names::<T>()?;
+        let columns = without_columns(T::to_column_names(), &T::to_read_only_columns());
+        mutation::build_update(
+            self.0.dialect(),
+            &table,
+            &columns,
+            &primary_columns,
+            &entity.to_dao(),
+        )
+    }
+
+    /// the primary key column names of the entity, or of its table as defined in the database
+    /// when there is no `#[rustorm(primary_key)]` in the entity
+    fn get_entity_primary_column_names<T>(&mut self) -> Result<Vec<String>, DbError>
+    where
+        T: ToTableName + ToPrimaryKey,
+    {
+        let primary_key = T::to_primary_key();
+        if primary_key.is_empty() {
+            self.get_primary_column_names(&T::to_table_name())
+        } else {
+            Ok(primary_key.into_iter().map(|column| column.name).collect())
+        }
+    }
+
+    /// the columns which identify the inserted records in the platforms that have no
+    /// `RETURNING`. A table without a primary key is identified with its row id, which is
+    /// the `ROWID` in sqlite and the `AUTO_INCREMENT` column in mysql
+    fn get_inserted_key_columns<T>(&mut self) -> Result<Vec<String>, DbError>
+    where
+        T: ToTableName + ToPrimaryKey,
+    {
+        match self.get_entity_primary_column_names::<T>() {
+            Err(DbError::DataError(DataError::NoPrimaryKey(table_name))) => {
+                if let Some(row_id) = self.0.dialect().row_id() {
+                    return Ok(vec![row_id.to_string()]);
+                }
+                let auto_increment = self.get_table(&T::to_table_name())?.and_then(|table| {
+                    table
+                        .columns
+                        .into_iter()
+                        .find(|column| column.is_autoincrement())
+                });
+                match auto_increment {
+                    Some(column) => Ok(vec![column.name.name]),
+                    None => Err(DataError::NoPrimaryKey(table_name).into()),
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// the primary key column names of the table, as defined in the database
+    fn get_primary_column_names(&mut self, table_name: &TableName) -> Result<Vec<String>, DbError> {
+        let table = self
+            .get_table(table_name)?
+            .ok_or_else(|| DataError::TableNameNotFound(table_name.complete_name()))?;
+        // the columns are kept in the order of the key, which is the order of the values
+        // of a composite key
+        let primary_columns: Vec<String> = table
+            .table_key
+            .iter()
+            .filter_map(|key| {
+                match key {
+                    TableKey::PrimaryKey(primary_key) => Some(&primary_key.columns),
+                    _ => None,
+                }
+            })
+            .flatten()
+            .map(|column| column.name.to_string())
+            .collect();
+        if primary_columns.is_empty() {
+            Err(DataError::NoPrimaryKey(table_name.complete_name()).into())
+        } else {
+            Ok(primary_columns)
+        }
+    }
+
     /// build the returning clause
     fn build_returning_clause(&self, return_columns: Vec<rustorm_dao::ColumnName>) -> String {
+        let dialect = self.0.dialect();
         format!(
             "\nRETURNING \n{}",
             return_columns
                 .iter()
-                .map(|rc| rc.name.to_owned())
+                .map(|rc| dialect.safe_identifier(&rc.name))
                 .collect::<Vec<_>>()
                 .join(", ")
         )
     }
 
-    /// build an insert clause
-    fn build_insert_clause<T>(&self, entities: &[&T]) -> String
-    where
-        T: ToTableName + ToColumnNames + ToDao,
-    {
-        let table = T::to_table_name();
-        let columns = T::to_column_names();
+    /// build an insert clause of the columns, for the number of rows. The `insert` is the
+    /// `INSERT` keyword or a variant of it such as `INSERT IGNORE`
+    fn build_insert_clause(
+        &self,
+        insert: &str,
+        table: &TableName,
+        columns: &[ColumnName],
+        rows: usize,
+    ) -> String {
+        let dialect = self.0.dialect();
         let columns_len = columns.len();
         let mut sql = String::new();
-        sql += &format!("INSERT INTO {} ", table.complete_name());
+        sql += &format!("{} INTO {} ", insert, dialect.table_name(table));
         sql += &format!(
             "({})\n",
             columns
                 .iter()
-                .map(|c| c.name.to_owned())
+                .map(|c| dialect.safe_identifier(&c.name))
                 .collect::<Vec<_>>()
                 .join(", ")
         );
         sql += "VALUES ";
-        sql += &entities
-            .iter()
-            .enumerate()
-            .map(|(y, _)| {
+        sql += &(0..rows)
+            .map(|y| {
                 format!(
                     "\n\t({})",
-                    columns
-                        .iter()
-                        .enumerate()
-                        .map(|(x, _)| {
-                            #[allow(unreachable_patterns)]
-                            match self.0 {
-                                #[cfg(feature = "with-sqlite")]
-                                DBPlatform::Sqlite(_) => format!("${}", y * columns_len + x + 1),
-                                #[cfg(feature = "with-postgres")]
-                                DBPlatform::Postgres(_) => format!("${}", y * columns_len + x + 1),
-                                #[cfg(feature = "with-mysql")]
-                                DBPlatform::Mysql(_) => "?".to_string(),
-                                _ => format!("${}", y * columns_len + x + 1),
-                            }
-                        })
+                    (0..columns_len)
+                        .map(|x| dialect.placeholder(y * columns_len + x + 1))
                         .collect::<Vec<_>>()
                         .join(", ")
                 )
@@ -319,12 +994,50 @@ impl EntityManager {
         params: &[&'a dyn ToValue],
     ) -> Result<Vec<R>, DbError>
     where
-        R: FromDao,
+        R: TryFromDao,
     {
         let values: Vec<Value> = params.iter().map(|p| p.to_value()).collect();
         let bvalues: Vec<&Value> = values.iter().collect();
         let rows = self.0.execute_sql_with_return(sql, &bvalues)?;
-        Ok(rows.iter().map(|dao| R::from_dao(&dao)).collect::<Vec<R>>())
+        Ok(rows
+            .iter()
+            .map(|dao| R::try_from_dao(&dao))
+            .collect::<Result<Vec<R>, _>>()?)
+    }
+
+    /// iterate over the records returned by the sql, each record is converted as it is
+    /// retrieved from the database
+    pub fn query_iter<'a, R>(
+        &'a mut self,
+        sql: &str,
+        params: &[&dyn ToValue],
+    ) -> Result<impl Iterator<Item = Result<R, DbError>> + 'a, DbError>
+    where
+        R: TryFromDao + 'a,
+    {
+        let values: Vec<Value> = params.iter().map(|p| p.to_value()).collect();
+        let bvalues: Vec<&Value> = values.iter().collect();
+        let daos = self.0.query_iter(sql, &bvalues)?;
+        Ok(daos.map(|dao| Ok(R::try_from_dao(&dao?)?)))
+    }
+
+    /// declare a server side cursor for the sql which fetches the records in chunks of
+    /// `fetch_size` rows, this has to be done inside a transaction.
+    /// This is only supported in platforms that has cursors such as postgresql
+    pub fn declare_cursor(
+        &mut self,
+        sql: &str,
+        params: &[&dyn ToValue],
+        fetch_size: usize,
+    ) -> Result<Cursor<'_>, DbError> {
+        if !self.0.dialect().supports_cursors() {
+            return Err(DbError::UnsupportedOperation(
+                "cursors are not supported in this platform".to_string(),
+            ));
+        }
+        let values: Vec<Value> = params.iter().map(|p| p.to_value()).collect();
+        let bvalues: Vec<&Value> = values.iter().collect();
+        Cursor::declare(&mut *self.0, sql, &bvalues, fetch_size)
     }
 
     pub fn raw_execute_sql_with_return(
@@ -341,7 +1054,7 @@ impl EntityManager {
         params: &[&'a dyn ToValue],
     ) -> Result<R, DbError>
     where
-        R: FromDao,
+        R: TryFromDao,
     {
         let result: Result<Vec<R>, DbError> = self.execute_sql_with_return(sql, params);
         match result {
@@ -362,7 +1075,7 @@ impl EntityManager {
         params: &[&'a dyn ToValue],
     ) -> Result<Option<R>, DbError>
     where
-        R: FromDao,
+        R: TryFromDao,
     {
         let result: Result<Vec<R>, DbError> = self.execute_sql_with_return(sql, params);
         match result {
@@ -377,3 +1090,175 @@ impl EntityManager {
         }
     }
 }
+
+/// the columns of the entity which are inserted, the values of the generated and
+/// read only columns are left to the database
+fn insert_columns<T>() -> Vec<ColumnName>
+where
+    T: ToColumnNames + ToPrimaryKey,
+{
+    let mut excluded = T::to_generated_columns();
+    excluded.extend(T::to_read_only_columns());
+    without_columns(T::to_column_names(), &excluded)
+}
+
+fn without_columns(columns: Vec<ColumnName>, excluded: &[ColumnName]) -> Vec<ColumnName> {
+    columns
+        .into_iter()
+        .filter(|column| !excluded.iter().any(|e| e.name == column.name))
+        .collect()
+}
+
+/// the primary key values of the dao when all of them are inserted and not null
+fn inserted_key_values(
+    columns: &[ColumnName],
+    primary_columns: &[String],
+    dao: &Dao,
+) -> Option<Vec<Value>> {
+    primary_columns
+        .iter()
+        .map(|column| {
+            match dao.get_value(column) {
+                Some(value) if !is_generated(columns, column, dao) => Some(value.clone()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// sort the retrieved records in the order of the keys of the inserted ones. The keys are
+/// compared as text since the retrieved values can be of a wider type, the records that
+/// are not matched are kept at the end
+fn in_key_order(daos: Vec<Dao>, primary_columns: &[String], keys: &[Vec<Value>]) -> Vec<Dao> {
+    let key_text = |values: Vec<String>| values.join("\u{0}");
+    let mut retrieved: Vec<(String, Dao)> = daos
+        .into_iter()
+        .map(|dao| {
+            let key = primary_columns
+                .iter()
+                .map(|column| dao.get_value(column).map(ToString::to_string).unwrap_or_default())
+                .collect();
+            (key_text(key), dao)
+        })
+        .collect();
+    let mut ordered = Vec::with_capacity(retrieved.len());
+    for key in keys {
+        let key = key_text(key.iter().map(ToString::to_string).collect());
+        if let Some(index) = retrieved.iter().position(|(retrieved_key, _)| *retrieved_key == key) {
+            ordered.push(retrieved.remove(index).1);
+        }
+    }
+    ordered.extend(retrieved.into_iter().map(|(_, dao)| dao));
+    ordered
+}
+
+/// the value of the column is generated by the database, since it is not inserted or is
+/// inserted as null
+fn is_generated(columns: &[ColumnName], column: &str, dao: &Dao) -> bool {
+    let inserted = columns.iter().any(|c| c.name == column);
+    !inserted || matches!(dao.get_value(column), None | Some(Value::Nil))
+}
+
+/// the values of the columns in the dao, null if there is none
+fn column_values(columns: &[ColumnName], dao: &Dao) -> Vec<Value> {
+    columns
+        .iter()
+        .map(|column| dao.get_value(&column.name).cloned().unwrap_or(Value::Nil))
+        .collect()
+}
+
+/// collect the values into an array, if all of them are of the same type that can be in an array
+fn to_array(values: &[Value]) -> Option<Array> {
+    macro_rules! collect {
+        ($variant:ident) => {
+            values
+                .iter()
+                .map(|v| {
+                    match v {
+                        Value::$variant(v) => Some(Some(v.to_owned())),
+                        _ => None,
+                    }
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(Array::$variant)
+        };
+    }
+    match values.first() {
+        None => Some(Array::Int(vec![])),
+        Some(Value::Bool(_)) => collect!(Bool),
+        Some(Value::Smallint(_)) => collect!(Smallint),
+        Some(Value::Int(_)) => collect!(Int),
+        Some(Value::Bigint(_)) => collect!(Bigint),
+        Some(Value::Float(_)) => collect!(Float),
+        Some(Value::Double(_)) => collect!(Double),
+        Some(Value::BigDecimal(_)) => collect!(BigDecimal),
+        Some(Value::Text(_)) => collect!(Text),
+        Some(Value::Uuid(_)) => collect!(Uuid),
+        Some(Value::Date(_)) => collect!(Date),
+        Some(Value::Timestamp(_)) => collect!(Timestamp),
+        Some(_) => None,
+    }
+}
+
+fn check_per_page(per_page: usize) -> Result<(), DbError> {
+    if per_page == 0 {
+        return Err(DataError::InvalidPerPage(per_page).into());
+    }
+    Ok(())
+}
+
+/// remove the total column which is added to the paginated query, and get its value
+fn take_total(rows: &mut Rows) -> Result<Option<usize>, DbError> {
+    let index = match rows.columns.iter().position(|column| column == TOTAL_COLUMN) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    rows.columns.remove(index);
+    let mut total = None;
+    for record in rows.data.iter_mut() {
+        let value = record.remove(index);
+        if total.is_none() {
+            total = Some(i64::from_value(&value).map_err(DaoError::ConvertError)? as usize);
+        }
+    }
+    Ok(total)
+}
+
+/// the page of the records, the next cursor is taken from the last record when the query is
+/// ordered by plain columns
+fn to_page<T>(
+    select: &Select<T>,
+    rows: Rows,
+    per_page: usize,
+    has_next: bool,
+) -> Result<Page<T>, DbError>
+where
+    T: TryFromDao,
+{
+    let next_cursor = match rows.iter().last() {
+        Some(last) if has_next && !select.order().is_empty() => {
+            select
+                .order()
+                .iter()
+                .map(|order| {
+                    match &order.expr {
+                        Expr::Column(column) => last.get_value(&column.name).cloned(),
+                        Expr::Raw(_) => None,
+                    }
+                })
+                .collect()
+        }
+        _ => None,
+    };
+    let items = rows
+        .iter()
+        .map(|dao| T::try_from_dao(&dao))
+        .collect::<Result<Vec<T>, _>>()?;
+    Ok(Page {
+        items,
+        per_page,
+        total: rows.count,
+        has_next,
+        next_cursor,
+    })
+}
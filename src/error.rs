@@ -1,5 +1,6 @@
 use cfg_if::cfg_if;
 use r2d2;
+use rustorm_dao::DaoError;
 use thiserror::Error;
 use url;
 
@@ -14,6 +15,7 @@ cfg_if! {if #[cfg(feature = "with-sqlite")]{
 
 cfg_if! {if #[cfg(feature = "with-mysql")]{
     use crate::my::MysqlError;
+    use r2d2_mysql::mysql;
 }}
 
 #[derive(Debug, Error)]
@@ -94,16 +96,50 @@ impl From<PlatformError> for DataOpError {
             }
             #[cfg(feature = "with-sqlite")]
             PlatformError::SqliteError(e) => {
-                DataOpError::GenericError {
-                    message: e.to_string(),
-                    sql: None,
+                match e {
+                    SqliteError::Sql(rusqlite::Error::SqliteFailure(failure, message), sql) => {
+                        DataOpError::ConstraintError {
+                            severity: "ERROR".to_string(),
+                            code: failure.extended_code.to_string(),
+                            message: message.unwrap_or_else(|| failure.to_string()),
+                            detail: None,
+                            cause_table: None,
+                            constraint: None,
+                            column: None,
+                            datatype: None,
+                            sql,
+                        }
+                    }
+                    _ => {
+                        DataOpError::GenericError {
+                            message: e.to_string(),
+                            sql: None,
+                        }
+                    }
                 }
             }
             #[cfg(feature = "with-mysql")]
             PlatformError::MysqlError(e) => {
-                DataOpError::GenericError {
-                    message: e.to_string(),
-                    sql: None,
+                match e {
+                    MysqlError::Sql(mysql::Error::MySqlError(my_err), sql) => {
+                        DataOpError::ConstraintError {
+                            severity: "ERROR".to_string(),
+                            code: my_err.code.to_string(),
+                            message: my_err.message,
+                            detail: None,
+                            cause_table: None,
+                            constraint: None,
+                            column: None,
+                            datatype: None,
+                            sql,
+                        }
+                    }
+                    _ => {
+                        DataOpError::GenericError {
+                            message: e.to_string(),
+                            sql: None,
+                        }
+                    }
                 }
             }
         }
@@ -145,11 +181,39 @@ pub enum DbError {
     #[error("{0}")]
     ConvertError(#[from] ConvertError),
     #[error("{0}")]
+    DaoError(#[from] DaoError),
+    #[error("{0}")]
     ConnectError(#[from] ConnectError), //agnostic connection error
     #[error("Unsupported operation: {0}")]
     UnsupportedOperation(String),
 }
 
+impl DbError {
+    /// whether the error is transient and the transaction that caused it
+    /// can be run again, such as a serialization failure or a deadlock
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DbError::DataOpError(DataOpError::ConstraintError { code, .. }) => {
+                is_retryable_code(code)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// the error codes of the supported platforms which are safe to retry
+fn is_retryable_code(code: &str) -> bool {
+    matches!(
+        code,
+        // postgres serialization_failure and deadlock_detected
+        "40001" | "40P01"
+        // mysql ER_LOCK_DEADLOCK
+        | "1213"
+        // sqlite SQLITE_BUSY, SQLITE_BUSY_RECOVERY, SQLITE_BUSY_SNAPSHOT, SQLITE_BUSY_TIMEOUT
+        | "5" | "261" | "517" | "773"
+    )
+}
+
 #[derive(Debug, Error)]
 pub enum DataOpError {
     /// The Data Delete Operation failed due record is still referenced from another table
@@ -178,6 +242,14 @@ pub enum ConvertError {
     UnknownDataType,
     #[error("Unsupported data type {0}")]
     UnsupportedDataType(String),
+    #[error("Unable to convert {value} to {to}")]
+    UnsupportedConversion { value: String, to: String },
+    #[error("Unable to convert the column {column} of type {sql_type}: {message}")]
+    ColumnConversion {
+        column: String,
+        sql_type: String,
+        message: String,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -188,4 +260,59 @@ pub enum DataError {
     MoreThan1RecordReturned,
     #[error("Table {0} not found")]
     TableNameNotFound(String),
+    #[error("Table {0} has no primary key")]
+    NoPrimaryKey(String),
+    #[error("No value for the primary key column {0}")]
+    MissingPrimaryKeyValue(String),
+    #[error("The primary key has {expected} columns, but {found} values are given")]
+    PrimaryKeyMismatch { expected: usize, found: usize },
+    #[error("The query is ordered by {expected} expressions, but the cursor has {found} values")]
+    CursorMismatch { expected: usize, found: usize },
+    #[error("Page {0} is out of range, the pages start at 1")]
+    PageOutOfRange(usize),
+    #[error("Invalid number of records per page {0}, a page has at least one record")]
+    InvalidPerPage(usize),
+    #[error("Column {0} not found")]
+    ColumnNameNotFound(String),
+    #[error("Column {0} is not an enum")]
+    NotAnEnum(String),
+    #[error("The enum {enum_name} does not match the column {column}, the variants that are not in the column: {unknown:?}, the choices that are not in the enum: {missing:?}")]
+    EnumMismatch {
+        enum_name: String,
+        column: String,
+        unknown: Vec<String>,
+        missing: Vec<String>,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn constraint_error(code: &str) -> DbError {
+        DbError::DataOpError(DataOpError::ConstraintError {
+            severity: "ERROR".to_string(),
+            code: code.to_string(),
+            message: "error".to_string(),
+            detail: None,
+            cause_table: None,
+            constraint: None,
+            column: None,
+            datatype: None,
+            sql: "SELECT 1".to_string(),
+        })
+    }
+
+    #[test]
+    fn retryable_errors() {
+        assert!(constraint_error("40001").is_retryable());
+        assert!(constraint_error("40P01").is_retryable());
+        assert!(constraint_error("1213").is_retryable());
+        assert!(constraint_error("5").is_retryable());
+        assert!(constraint_error("517").is_retryable());
+        assert!(!constraint_error("23505").is_retryable());
+        assert!(!constraint_error("1062").is_retryable());
+        assert!(!constraint_error("1555").is_retryable());
+        assert!(!DbError::UnsupportedOperation("savepoint".to_string()).is_retryable());
+    }
 }
@@ -0,0 +1,230 @@
+use crate::{
+    statement::SqlWriter,
+    Array,
+    ColumnName,
+    ToValue,
+    Value,
+};
+
+/// The left hand side of a filter, or an item in the `GROUP BY` and `ORDER BY` clause
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// a column, optionally qualified with the table name: `film.rating`
+    Column(ColumnName),
+    /// an sql expression which is written verbatim into the statement, such as `COUNT(*)`.
+    /// This is not escaped, never construct it from user input.
+    Raw(String),
+}
+
+impl Expr {
+    pub fn column(name: &str) -> Self { Expr::Column(ColumnName::from(name)) }
+
+    pub fn raw(sql: &str) -> Self { Expr::Raw(sql.to_string()) }
+
+    pub(crate) fn write_sql(&self, w: &mut SqlWriter) {
+        match self {
+            Expr::Column(column) => w.push(&w.dialect.column_name(column)),
+            Expr::Raw(sql) => w.push(sql),
+        }
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(name: &str) -> Self { Expr::column(name) }
+}
+
+impl From<ColumnName> for Expr {
+    fn from(column: ColumnName) -> Self { Expr::Column(column) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Like,
+}
+
+impl Operator {
+    fn to_sql(self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::Ne => "<>",
+            Operator::Lt => "<",
+            Operator::Lte => "<=",
+            Operator::Gt => ">",
+            Operator::Gte => ">=",
+            Operator::Like => "LIKE",
+        }
+    }
+}
+
+/// A typed condition used in the `WHERE` and `HAVING` clause.
+/// The values are always passed as bound parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Compare(Expr, Operator, Value),
+    In(Expr, Vec<Value>),
+    /// `expr = ANY(array)`, the array is bound as a single param.
+    /// This is only supported in postgresql
+    Any(Expr, Value),
+    IsNull(Expr),
+    IsNotNull(Expr),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn compare<E, V>(expr: E, operator: Operator, value: V) -> Self
+    where
+        E: Into<Expr>,
+        V: ToValue,
+    {
+        Filter::Compare(expr.into(), operator, value.to_value())
+    }
+
+    pub fn eq<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Eq, value)
+    }
+
+    pub fn ne<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Ne, value)
+    }
+
+    pub fn lt<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Lt, value)
+    }
+
+    pub fn lte<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Lte, value)
+    }
+
+    pub fn gt<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Gt, value)
+    }
+
+    pub fn gte<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Gte, value)
+    }
+
+    pub fn like<E: Into<Expr>, V: ToValue>(expr: E, value: V) -> Self {
+        Filter::compare(expr, Operator::Like, value)
+    }
+
+    pub fn is_in<E: Into<Expr>, V: ToValue>(expr: E, values: &[V]) -> Self {
+        Filter::In(expr.into(), values.iter().map(ToValue::to_value).collect())
+    }
+
+    pub fn any<E: Into<Expr>>(expr: E, array: Array) -> Self {
+        Filter::Any(expr.into(), Value::Array(array))
+    }
+
+    /// match the values of the primary key columns
+    pub(crate) fn primary_key(columns: &[String], values: Vec<Value>) -> Self {
+        let mut filters: Vec<Filter> = columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| Filter::Compare(Expr::column(column), Operator::Eq, value))
+            .collect();
+        match filters.len() {
+            1 => filters.remove(0),
+            _ => Filter::And(filters),
+        }
+    }
+
+    pub fn is_null<E: Into<Expr>>(expr: E) -> Self { Filter::IsNull(expr.into()) }
+
+    pub fn is_not_null<E: Into<Expr>>(expr: E) -> Self { Filter::IsNotNull(expr.into()) }
+
+    /// combine this filter with another one, both of which has to be satisfied
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            _ => Filter::And(vec![self, other]),
+        }
+    }
+
+    /// combine this filter with another one, either of which has to be satisfied
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            _ => Filter::Or(vec![self, other]),
+        }
+    }
+
+    pub(crate) fn write_sql(&self, w: &mut SqlWriter) {
+        match self {
+            Filter::Compare(expr, operator, value) => {
+                expr.write_sql(w);
+                w.push(&format!(" {} ", operator.to_sql()));
+                w.push_param(value.clone());
+            }
+            // an empty list matches nothing
+            Filter::In(_, values) if values.is_empty() => w.push(w.dialect.bool_literal(false)),
+            Filter::In(expr, values) => {
+                expr.write_sql(w);
+                w.push(" IN (");
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        w.push(", ");
+                    }
+                    w.push_param(value.clone());
+                }
+                w.push(")");
+            }
+            Filter::Any(expr, array) => {
+                expr.write_sql(w);
+                w.push(" = ANY(");
+                w.push_param(array.clone());
+                w.push(")");
+            }
+            Filter::IsNull(expr) => {
+                expr.write_sql(w);
+                w.push(" IS NULL");
+            }
+            Filter::IsNotNull(expr) => {
+                expr.write_sql(w);
+                w.push(" IS NOT NULL");
+            }
+            Filter::And(filters) => write_joined(w, filters, " AND ", true),
+            Filter::Or(filters) => write_joined(w, filters, " OR ", false),
+            Filter::Not(filter) => {
+                w.push("NOT (");
+                filter.write_sql(w);
+                w.push(")");
+            }
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output { Filter::Not(Box::new(self)) }
+}
+
+/// write each of the filters in parenthesis, separated with the `AND`/`OR` keyword.
+/// No filter at all is written as the `empty` boolean literal.
+fn write_joined(w: &mut SqlWriter, filters: &[Filter], separator: &str, empty: bool) {
+    if filters.is_empty() {
+        w.push(w.dialect.bool_literal(empty));
+    }
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
+            w.push(separator);
+        }
+        w.push("(");
+        filter.write_sql(w);
+        w.push(")");
+    }
+}
@@ -31,34 +31,64 @@ cfg_if! {if #[cfg(feature = "with-mysql")]{
 
 pub mod column;
 pub mod common;
+mod cursor;
 mod dao_manager;
 mod database;
 #[cfg(feature = "db-auth")]
 mod db_auth;
+pub mod dialect;
 mod entity;
 pub mod error;
+mod filter;
+mod mutation;
+mod page;
 mod platform;
 pub mod pool;
+mod select;
+mod statement;
 pub mod table;
+mod transaction;
 pub mod types;
 
 pub mod util;
 
 pub use chrono;
 pub use column::ColumnDef;
+pub use cursor::Cursor;
 pub use dao_manager::DaoManager;
 pub use database::{
+    DaoIter,
     Database,
     DatabaseName,
 };
+pub use dialect::Dialect;
 pub use entity::EntityManager;
 pub use error::{
     DataError,
     DbError,
 };
+pub use filter::{
+    Expr,
+    Filter,
+    Operator,
+};
+pub use mutation::UpsertAction;
+pub use page::Page;
 pub use platform::DBPlatform;
 pub use pool::Pool;
+pub use select::{
+    Direction,
+    Order,
+    Select,
+};
+pub use statement::Statement;
 pub use table::TableDef;
+pub use transaction::{
+    IsolationLevel,
+    RetryPolicy,
+    Transaction,
+    TransactionOptions,
+};
 pub use uuid::{
     self,
     Uuid,
@@ -68,9 +98,12 @@ pub use uuid::{
 // this are used in the apps
 pub use codegen::{
     FromDao,
+    FromValue,
     ToColumnNames,
     ToDao,
+    ToPrimaryKey,
     ToTableName,
+    ToValue,
 };
 
 pub use rustorm_dao::{
@@ -79,20 +112,34 @@ pub use rustorm_dao::{
     ColumnName,
     ConvertError,
     Dao,
+    DaoError,
     FromValue,
+    Json,
     Rows,
     TableName,
+    ToEnumVariants,
+    ToKeyValues,
     ToValue,
+    TryFromDao,
     Value,
 };
 
 /// Wrap the rustorm_dao exports to avoid name conflict with the rustorm_codegen
 pub mod dao {
     pub use rustorm_dao::{
+        json,
+        ConvertError,
+        DaoError,
         FromDao,
+        FromValue,
         ToColumnNames,
         ToDao,
+        ToEnumVariants,
+        ToPrimaryKey,
         ToTableName,
+        ToValue,
+        TryFromDao,
+        Value,
     };
 }
 
@@ -100,9 +147,12 @@ pub mod dao {
 pub mod codegen {
     pub use rustorm_codegen::{
         FromDao,
+        FromValue,
         ToColumnNames,
         ToDao,
+        ToPrimaryKey,
         ToTableName,
+        ToValue,
     };
 }
 
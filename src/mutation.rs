@@ -0,0 +1,272 @@
+//! Building the sql of the statements which modify the records of a table
+use crate::{
+    statement::{
+        SqlWriter,
+        Statement,
+    },
+    ColumnName,
+    Dao,
+    DataError,
+    DbError,
+    Dialect,
+    Filter,
+    TableName,
+    Value,
+};
+
+/// What is done to the existing record that conflicts with the inserted one
+/// in `EntityManager::upsert`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpsertAction {
+    /// leave the existing record unchanged
+    DoNothing,
+    /// set these columns of the existing record to the values of the inserted one.
+    /// Updating no columns is the same as `DoNothing`
+    Update(Vec<String>),
+}
+
+impl UpsertAction {
+    pub fn update(columns: &[&str]) -> Self {
+        UpsertAction::Update(columns.iter().map(ToString::to_string).collect())
+    }
+}
+
+/// build an `UPDATE` statement which sets the values of the `columns` from the dao,
+/// to the record that has the same primary key
+pub(crate) fn build_update(
+    dialect: &dyn Dialect,
+    table: &TableName,
+    columns: &[ColumnName],
+    primary_columns: &[String],
+    dao: &Dao,
+) -> Result<Statement, DbError> {
+    let filter = primary_key_filter(primary_columns, dao)?;
+    let mut set_columns: Vec<&ColumnName> = columns
+        .iter()
+        .filter(|column| !primary_columns.contains(&column.name))
+        .collect();
+    // the entity only has the primary key, it is set to itself so that the
+    // matched record is still counted
+    if set_columns.is_empty() {
+        set_columns = columns.iter().collect();
+    }
+    let assignments = set_columns.iter().map(|column| {
+        let value = dao.get_value(&column.name).cloned().unwrap_or(Value::Nil);
+        (column.name.as_str(), value)
+    });
+    Ok(write_update(dialect, table, assignments, &filter))
+}
+
+/// build an `UPDATE` statement which sets the columns to the values of the `set` dao,
+/// to all the records that match the filter
+pub(crate) fn build_update_where(
+    dialect: &dyn Dialect,
+    table: &TableName,
+    set: &Dao,
+    filter: &Filter,
+) -> Result<Statement, DbError> {
+    if set.0.is_empty() {
+        return Err(DbError::UnsupportedOperation(
+            "update without any column to set".to_string(),
+        ));
+    }
+    let assignments = set
+        .0
+        .iter()
+        .map(|(column, value)| (column.as_str(), value.clone()));
+    Ok(write_update(dialect, table, assignments, filter))
+}
+
+fn write_update<'a, I>(
+    dialect: &dyn Dialect,
+    table: &TableName,
+    assignments: I,
+    filter: &Filter,
+) -> Statement
+where
+    I: Iterator<Item = (&'a str, Value)>,
+{
+    let mut w = SqlWriter::new(dialect);
+    w.push("UPDATE ");
+    w.push(&dialect.table_name(table));
+    w.push(" SET ");
+    for (i, (column, value)) in assignments.enumerate() {
+        if i > 0 {
+            w.push(", ");
+        }
+        w.push_identifier(column);
+        w.push(" = ");
+        w.push_param(value);
+    }
+    w.push(" WHERE ");
+    filter.write_sql(&mut w);
+    w.finish()
+}
+
+/// build a `DELETE` statement for the record that has the same primary key as the dao
+pub(crate) fn build_delete(
+    dialect: &dyn Dialect,
+    table: &TableName,
+    primary_columns: &[String],
+    dao: &Dao,
+) -> Result<Statement, DbError> {
+    let filter = primary_key_filter(primary_columns, dao)?;
+    Ok(build_delete_where(dialect, table, &filter))
+}
+
+/// build a `DELETE` statement for all the records that match the filter
+pub(crate) fn build_delete_where(
+    dialect: &dyn Dialect,
+    table: &TableName,
+    filter: &Filter,
+) -> Statement {
+    let mut w = SqlWriter::new(dialect);
+    w.push("DELETE FROM ");
+    w.push(&dialect.table_name(table));
+    w.push(" WHERE ");
+    filter.write_sql(&mut w);
+    w.finish()
+}
+
+/// the filter which matches the primary key values of the dao
+fn primary_key_filter(primary_columns: &[String], dao: &Dao) -> Result<Filter, DbError> {
+    let mut values = Vec::with_capacity(primary_columns.len());
+    for column in primary_columns {
+        // a null key would match no record
+        match dao.get_value(column) {
+            Some(Value::Nil) | None => {
+                return Err(DataError::MissingPrimaryKeyValue(column.to_string()).into())
+            }
+            Some(value) => values.push(value.clone()),
+        }
+    }
+    Ok(Filter::primary_key(primary_columns, values))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dialect::{
+        MysqlDialect,
+        PostgresDialect,
+    };
+
+    fn actor() -> Dao {
+        let mut dao = Dao::new();
+        dao.insert("actor_id", 1);
+        dao.insert("first_name", "TOM");
+        dao.insert("last_name", "HANKS");
+        dao
+    }
+
+    fn columns() -> Vec<ColumnName> {
+        vec![
+            ColumnName::from("actor_id"),
+            ColumnName::from("first_name"),
+            ColumnName::from("last_name"),
+        ]
+    }
+
+    #[test]
+    fn update_by_primary_key() {
+        let statement = build_update(
+            &PostgresDialect,
+            &TableName::from("actor"),
+            &columns(),
+            &["actor_id".to_string()],
+            &actor(),
+        )
+        .unwrap();
+        assert_eq!(
+            statement.sql,
+            "UPDATE actor SET first_name = $1, last_name = $2 WHERE actor_id = $3"
+        );
+        assert_eq!(statement.params, vec![
+            Value::Text("TOM".into()),
+            Value::Text("HANKS".into()),
+            Value::Int(1)
+        ]);
+    }
+
+    #[test]
+    fn delete_by_composite_primary_key() {
+        let statement = build_delete(
+            &MysqlDialect,
+            &TableName::from("sakila.actor"),
+            &["actor_id".to_string(), "last_name".to_string()],
+            &actor(),
+        )
+        .unwrap();
+        assert_eq!(
+            statement.sql,
+            "DELETE FROM sakila.actor WHERE (actor_id = ?) AND (last_name = ?)"
+        );
+    }
+
+    #[test]
+    fn update_and_delete_where() {
+        let mut set = Dao::new();
+        set.insert("rental_rate", 0.99);
+        let statement = build_update_where(
+            &PostgresDialect,
+            &TableName::from("film"),
+            &set,
+            &Filter::eq("rating", "PG"),
+        )
+        .unwrap();
+        assert_eq!(statement.sql, "UPDATE film SET rental_rate = $1 WHERE rating = $2");
+        assert_eq!(statement.params, vec![
+            Value::Double(0.99),
+            Value::Text("PG".into())
+        ]);
+
+        let statement = build_delete_where(
+            &MysqlDialect,
+            &TableName::from("rental"),
+            &Filter::lt("return_date", "2005-06-01").and(Filter::is_not_null("customer_id")),
+        );
+        assert_eq!(
+            statement.sql,
+            "DELETE FROM rental WHERE (return_date < ?) AND (customer_id IS NOT NULL)"
+        );
+
+        let result = build_update_where(
+            &PostgresDialect,
+            &TableName::from("film"),
+            &Dao::new(),
+            &Filter::eq("rating", "PG"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn missing_primary_key_value() {
+        let result = build_delete(
+            &PostgresDialect,
+            &TableName::from("actor"),
+            &["id".to_string()],
+            &actor(),
+        );
+        match result {
+            Err(DbError::DataError(DataError::MissingPrimaryKeyValue(column))) => {
+                assert_eq!(column, "id")
+            }
+            _ => panic!("expecting a missing primary key value error"),
+        }
+        let mut dao = actor();
+        dao.insert("actor_id", None::<i32>);
+        let result = build_update(
+            &PostgresDialect,
+            &TableName::from("actor"),
+            &columns(),
+            &["actor_id".to_string()],
+            &dao,
+        );
+        match result {
+            Err(DbError::DataError(DataError::MissingPrimaryKeyValue(column))) => {
+                assert_eq!(column, "actor_id")
+            }
+            _ => panic!("expecting a missing primary key value error"),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::Value;
+
+/// A page of the records of a query, retrieved with `EntityManager::paginate`
+/// or `EntityManager::paginate_after`.
+///
+/// ```rust,ignore
+/// let select = Select::<Actor>::new().order_by("actor_id", Direction::Asc);
+/// let page: Page<Actor> = em.paginate(&select, 1, 20)?;
+/// println!("{} actors in {:?} pages", page.total.unwrap(), page.total_pages());
+///
+/// // keyset pagination continues from the last record of the previous page
+/// if let Some(cursor) = page.next_cursor {
+///     let next: Page<Actor> = em.paginate_after(&select.after(cursor), 20)?;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub per_page: usize,
+    /// the number of records matched by the query on all the pages,
+    /// this is only counted with `paginate`
+    pub total: Option<usize>,
+    /// there are more records after this page
+    pub has_next: bool,
+    /// the values of the `ORDER BY` columns of the last record, which is passed to
+    /// `Select::after` to retrieve the next page. This is `None` on the last page or
+    /// when the query is not ordered by plain columns
+    pub next_cursor: Option<Vec<Value>>,
+}
+
+impl<T> Page<T> {
+    /// the number of pages, when the total is known
+    pub fn total_pages(&self) -> Option<usize> {
+        self.total.map(|total| {
+            total / self.per_page + usize::from(total % self.per_page != 0)
+        })
+    }
+}
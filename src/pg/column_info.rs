@@ -361,15 +361,20 @@ fn get_column_specification(
             } else {
                 let sql_type = match &*dtype {
                     "boolean" => SqlType::Bool,
+                    "boolean[]" => SqlType::Array(Box::new(SqlType::Bool)),
                     "tinyint" => SqlType::Tinyint,
                     "smallint" | "year" => SqlType::Smallint,
+                    "smallint[]" => SqlType::Array(Box::new(SqlType::Smallint)),
                     "int" | "integer" => SqlType::Int,
                     "int[]" | "integer[]" => SqlType::Array(Box::new(SqlType::Int)),
                     "bigint" => SqlType::Bigint,
+                    "bigint[]" => SqlType::Array(Box::new(SqlType::Bigint)),
                     "real" => SqlType::Real,
                     "float" => SqlType::Float,
                     "double" | "double precision" => SqlType::Double,
+                    "double precision[]" => SqlType::Array(Box::new(SqlType::Double)),
                     "numeric" => SqlType::Numeric,
+                    "numeric[]" => SqlType::Array(Box::new(SqlType::Numeric)),
                     "tinyblob" => SqlType::Tinyblob,
                     "mediumblob" => SqlType::Mediumblob,
                     "blob" => SqlType::Blob,
@@ -388,9 +393,15 @@ fn get_column_specification(
                     "tsvector" => SqlType::TsVector,
                     "text[]" => SqlType::Array(Box::new(SqlType::Text)),
                     "uuid" => SqlType::Uuid,
+                    "uuid[]" => SqlType::Array(Box::new(SqlType::Uuid)),
                     "date" => SqlType::Date,
+                    "date[]" => SqlType::Array(Box::new(SqlType::Date)),
                     "timestamp" | "timestamp without time zone" => SqlType::Timestamp,
+                    "timestamp without time zone[]" => SqlType::Array(Box::new(SqlType::Timestamp)),
                     "timestamp with time zone" => SqlType::TimestampTz,
+                    "timestamp with time zone[]" => {
+                        SqlType::Array(Box::new(SqlType::TimestampTz))
+                    }
                     "time with time zone" => SqlType::TimeTz,
                     "time without time zone" => SqlType::Time,
                     "inet" => SqlType::IpAddress,
@@ -562,7 +573,7 @@ mod test {
     #[test]
     fn insert_text_array() {
         use log::*;
-        #[derive(Debug, ToDao, ToColumnNames, ToTableName)]
+        #[derive(Debug, ToDao, ToColumnNames, ToTableName, ToPrimaryKey)]
         struct Film {
             title: String,
             language_id: i16,
@@ -303,6 +303,7 @@ fn get_columnname_from_key(
          AND pg_class.relname = $2
          AND pg_attribute.attnum > 0
          AND pg_constraint.conname = $1
+    ORDER BY array_position(pg_constraint.conkey, pg_attribute.attnum)
         "#;
     let schema = match table_name.schema {
         Some(ref schema) => schema.to_string(),
@@ -1,6 +1,8 @@
 use crate::{
     error::ParseError,
+    dialect,
     Database,
+    Dialect,
 };
 use cfg_if::cfg_if;
 use std::{
@@ -30,6 +32,31 @@ pub enum DBPlatform {
     Mysql(Box<MysqlDB>),
 }
 
+impl DBPlatform {
+    /// the sql dialect of this platform
+    pub fn dialect(&self) -> &'static dyn Dialect {
+        match *self {
+            #[cfg(feature = "with-postgres")]
+            DBPlatform::Postgres(_) => &dialect::PostgresDialect,
+            #[cfg(feature = "with-sqlite")]
+            DBPlatform::Sqlite(_) => &dialect::SqliteDialect,
+            #[cfg(feature = "with-mysql")]
+            DBPlatform::Mysql(_) => &dialect::MysqlDialect,
+        }
+    }
+
+    /// the number of bound params that can be in a statement, which is read from the
+    /// connection in sqlite since it depends on the build of the library
+    pub fn max_params(&self) -> usize {
+        match *self {
+            #[cfg(feature = "with-sqlite")]
+            DBPlatform::Sqlite(ref sq) => sq.max_params(),
+            #[allow(unreachable_patterns)]
+            _ => self.dialect().max_params(),
+        }
+    }
+}
+
 impl Deref for DBPlatform {
     type Target = dyn Database;
 
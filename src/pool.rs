@@ -22,6 +22,7 @@ use crate::{
         ParseError,
     },
     platform::Platform,
+    transaction::TransactionDepth,
     DBPlatform,
     DaoManager,
     DbError,
@@ -266,14 +267,22 @@ impl Pool {
         match pooled_conn {
             #[cfg(feature = "with-postgres")]
             PooledConn::PooledPg(pooled_pg) => {
-                Ok(DBPlatform::Postgres(Box::new(PostgresDB(*pooled_pg))))
+                Ok(DBPlatform::Postgres(Box::new(PostgresDB(
+                    *pooled_pg,
+                    TransactionDepth::default(),
+                ))))
             }
             #[cfg(feature = "with-sqlite")]
             PooledConn::PooledSq(pooled_sq) => {
-                Ok(DBPlatform::Sqlite(Box::new(SqliteDB(*pooled_sq))))
+                Ok(DBPlatform::Sqlite(Box::new(SqliteDB(*pooled_sq, TransactionDepth::default()))))
             }
             #[cfg(feature = "with-mysql")]
-            PooledConn::PooledMy(pooled_my) => Ok(DBPlatform::Mysql(Box::new(MysqlDB(*pooled_my)))),
+            PooledConn::PooledMy(pooled_my) => {
+                Ok(DBPlatform::Mysql(Box::new(MysqlDB(
+                    *pooled_my,
+                    TransactionDepth::default(),
+                ))))
+            }
         }
     }
 
@@ -0,0 +1,529 @@
+use crate::{
+    filter::{
+        Expr,
+        Filter,
+        Operator,
+    },
+    statement::{
+        SqlWriter,
+        Statement,
+    },
+    ColumnName,
+    DataError,
+    DbError,
+    Dialect,
+    TableName,
+    Value,
+};
+use rustorm_dao::{
+    ToColumnNames,
+    ToTableName,
+};
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub expr: Expr,
+    pub direction: Direction,
+}
+
+/// A SELECT query on the table of `T`, retrieving the columns of `T`.
+///
+/// ```rust,ignore
+/// let select = Select::<Actor>::new()
+///     .filter(Filter::eq("first_name", "TOM"))
+///     .order_by("last_name", Direction::Asc)
+///     .limit(10);
+/// let actors: Vec<Actor> = em.select(&select)?;
+/// ```
+#[derive(Debug)]
+pub struct Select<T> {
+    table: TableName,
+    /// the column and the expression it is computed from, if it is not a plain column
+    columns: Vec<(ColumnName, Option<Expr>)>,
+    filter: Option<Filter>,
+    group_by: Vec<Expr>,
+    having: Option<Filter>,
+    order_by: Vec<Order>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    /// the values of the `ORDER BY` expressions of the record before the first one retrieved
+    after: Vec<Value>,
+    entity: PhantomData<T>,
+}
+
+/// the column of the total number of records that is added to the paginated queries
+pub(crate) const TOTAL_COLUMN: &str = "rustorm_total";
+
+/// the entity doesn't need to be `Clone` for the query to be cloned
+impl<T> Clone for Select<T> {
+    fn clone(&self) -> Self {
+        Select {
+            table: self.table.clone(),
+            columns: self.columns.clone(),
+            filter: self.filter.clone(),
+            group_by: self.group_by.clone(),
+            having: self.having.clone(),
+            order_by: self.order_by.clone(),
+            limit: self.limit,
+            offset: self.offset,
+            after: self.after.clone(),
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<T> Select<T>
+where
+    T: ToTableName + ToColumnNames,
+{
+    pub fn new() -> Self { Select::from_table(T::to_table_name()) }
+}
+
+impl<T> Select<T>
+where
+    T: ToColumnNames,
+{
+    /// select the columns of `T` from the table, for when `T` is not the entity of the table
+    pub(crate) fn from_table(table: TableName) -> Self {
+        Select {
+            table,
+            columns: T::to_column_names()
+                .into_iter()
+                .map(|column| (column, None))
+                .collect(),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+            after: vec![],
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Select<T>
+where
+    T: ToTableName + ToColumnNames,
+{
+    fn default() -> Self { Select::new() }
+}
+
+impl<T> Select<T> {
+    /// retrieve the column `name` of `T` from an expression instead,
+    /// such as an aggregate `COUNT(*)` in a grouped query
+    pub fn column_expr(mut self, name: &str, expr: Expr) -> Self {
+        for (column, column_expr) in self.columns.iter_mut() {
+            if column.name == name {
+                *column_expr = Some(expr.clone());
+            }
+        }
+        self
+    }
+
+    /// add a condition to the `WHERE` clause, this is combined with the previous
+    /// conditions using `AND`
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(match self.filter {
+            Some(existing) => existing.and(filter),
+            None => filter,
+        });
+        self
+    }
+
+    pub fn group_by<E: Into<Expr>>(mut self, expr: E) -> Self {
+        self.group_by.push(expr.into());
+        self
+    }
+
+    /// add a condition to the `HAVING` clause, this is combined with the previous
+    /// conditions using `AND`
+    pub fn having(mut self, filter: Filter) -> Self {
+        self.having = Some(match self.having {
+            Some(existing) => existing.and(filter),
+            None => filter,
+        });
+        self
+    }
+
+    pub fn order_by<E: Into<Expr>>(mut self, expr: E, direction: Direction) -> Self {
+        self.order_by.push(Order {
+            expr: expr.into(),
+            direction,
+        });
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// keyset pagination: retrieve only the records that come after the record which has
+    /// these values for the `ORDER BY` expressions, in the same order. This is the
+    /// `next_cursor` of a `Page`. The ordering should be unique and have no nulls
+    pub fn after(mut self, cursor: Vec<Value>) -> Self {
+        self.after = cursor;
+        self
+    }
+
+    pub(crate) fn order(&self) -> &[Order] { &self.order_by }
+
+    /// the `after` cursor has a value for each of the `ORDER BY` expressions
+    pub(crate) fn check_cursor(&self) -> Result<(), DbError> {
+        if !self.after.is_empty() && self.after.len() != self.order_by.len() {
+            return Err(DataError::CursorMismatch {
+                expected: self.order_by.len(),
+                found: self.after.len(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// also select the number of records matched by the query regardless of the limit,
+    /// using a window function
+    pub(crate) fn with_total(mut self) -> Self {
+        self.columns.push((
+            ColumnName::from(TOTAL_COLUMN),
+            Some(Expr::raw("COUNT(*) OVER()")),
+        ));
+        self
+    }
+
+    /// the statement which counts the records matched by the query regardless of the
+    /// ordering, limit and offset
+    pub(crate) fn build_count(&self, dialect: &dyn Dialect) -> Statement {
+        let mut query = self.clone();
+        query.order_by.clear();
+        query.limit = None;
+        query.offset = None;
+        let inner = query.build(dialect);
+        Statement {
+            sql: format!("SELECT COUNT(*) AS count FROM ({}) AS rustorm_count", inner.sql),
+            params: inner.params,
+        }
+    }
+
+    /// `(a > $1) OR (a = $1 AND b > $2)` for the keyset `(a, b)`, using `<` for the
+    /// descending order
+    fn keyset_filter(&self) -> Option<Filter> {
+        if self.after.is_empty() {
+            return None;
+        }
+        let mut filters: Vec<Filter> = vec![];
+        for (i, (order, value)) in self.order_by.iter().zip(&self.after).enumerate() {
+            let operator = match order.direction {
+                Direction::Asc => Operator::Gt,
+                Direction::Desc => Operator::Lt,
+            };
+            let mut conditions: Vec<Filter> = self.order_by[..i]
+                .iter()
+                .zip(&self.after)
+                .map(|(order, value)| {
+                    Filter::Compare(order.expr.clone(), Operator::Eq, value.clone())
+                })
+                .collect();
+            conditions.push(Filter::Compare(order.expr.clone(), operator, value.clone()));
+            filters.push(match conditions.len() {
+                1 => conditions.remove(0),
+                _ => Filter::And(conditions),
+            });
+        }
+        Some(match filters.len() {
+            1 => filters.remove(0),
+            _ => Filter::Or(filters),
+        })
+    }
+
+    /// build the sql statement with its params
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let mut w = SqlWriter::new(dialect);
+        w.push("SELECT ");
+        for (i, (column, expr)) in self.columns.iter().enumerate() {
+            if i > 0 {
+                w.push(", ");
+            }
+            match expr {
+                Some(expr) => {
+                    expr.write_sql(&mut w);
+                    w.push(" AS ");
+                    w.push_identifier(&column.name);
+                }
+                None => w.push_identifier(&column.name),
+            }
+        }
+        w.push(" FROM ");
+        w.push(&dialect.table_name(&self.table));
+        let filter = match (self.filter.clone(), self.keyset_filter()) {
+            (Some(filter), Some(keyset)) => Some(filter.and(keyset)),
+            (filter, keyset) => filter.or(keyset),
+        };
+        if let Some(filter) = &filter {
+            w.push(" WHERE ");
+            filter.write_sql(&mut w);
+        }
+        if !self.group_by.is_empty() {
+            w.push(" GROUP BY ");
+            for (i, expr) in self.group_by.iter().enumerate() {
+                if i > 0 {
+                    w.push(", ");
+                }
+                expr.write_sql(&mut w);
+            }
+        }
+        if let Some(having) = &self.having {
+            w.push(" HAVING ");
+            having.write_sql(&mut w);
+        }
+        if !self.order_by.is_empty() {
+            w.push(" ORDER BY ");
+            for (i, order) in self.order_by.iter().enumerate() {
+                if i > 0 {
+                    w.push(", ");
+                }
+                order.expr.write_sql(&mut w);
+                match order.direction {
+                    Direction::Asc => w.push(" ASC"),
+                    Direction::Desc => w.push(" DESC"),
+                }
+            }
+        }
+        let limit_offset = dialect.limit_offset(self.limit, self.offset);
+        if !limit_offset.is_empty() {
+            w.push(" ");
+            w.push(&limit_offset);
+        }
+        w.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        codegen::{
+            ToColumnNames,
+            ToTableName,
+        },
+        dialect::{
+            MysqlDialect,
+            PostgresDialect,
+            SqliteDialect,
+        },
+        Array,
+        Value,
+    };
+
+    #[derive(ToTableName, ToColumnNames)]
+    #[allow(dead_code)]
+    struct Actor {
+        actor_id: i32,
+        first_name: String,
+        last_name: String,
+    }
+
+    #[derive(ToTableName, ToColumnNames)]
+    #[table_name = "film"]
+    #[allow(dead_code)]
+    struct RatingCount {
+        rating: String,
+        count: i64,
+    }
+
+    #[test]
+    fn select_all() {
+        let statement = Select::<Actor>::new().build(&PostgresDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor"
+        );
+        assert!(statement.params.is_empty());
+    }
+
+    #[test]
+    fn select_with_filter_order_limit() {
+        let statement = Select::<Actor>::new()
+            .filter(Filter::eq("first_name", "TOM"))
+            .filter(Filter::gt("actor_id", 10).or(Filter::is_null("last_name")))
+            .order_by("last_name", Direction::Desc)
+            .limit(10)
+            .offset(20)
+            .build(&PostgresDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor \
+             WHERE (first_name = $1) AND ((actor_id > $2) OR (last_name IS NULL)) \
+             ORDER BY last_name DESC LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(statement.params, vec![
+            Value::Text("TOM".into()),
+            Value::Int(10)
+        ]);
+    }
+
+    #[test]
+    fn select_group_by_having() {
+        let statement = Select::<RatingCount>::new()
+            .column_expr("count", Expr::raw("COUNT(*)"))
+            .filter(Filter::is_in("rating", &["G", "PG"]))
+            .group_by("rating")
+            .having(Filter::gt(Expr::raw("COUNT(*)"), 100))
+            .build(&MysqlDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT rating, COUNT(*) AS count FROM film \
+             WHERE rating IN (?, ?) GROUP BY rating HAVING COUNT(*) > ?"
+        );
+        assert_eq!(statement.params, vec![
+            Value::Text("G".into()),
+            Value::Text("PG".into()),
+            Value::Int(100)
+        ]);
+    }
+
+    #[test]
+    fn select_by_keys() {
+        let statement = Select::<Actor>::new()
+            .filter(Filter::any("actor_id", Array::Int(vec![Some(1), Some(2), Some(3)])))
+            .build(&PostgresDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor WHERE actor_id = ANY($1)"
+        );
+        assert_eq!(
+            statement.params,
+            vec![Value::Array(Array::Int(vec![Some(1), Some(2), Some(3)]))]
+        );
+
+        let statement = Select::<Actor>::new()
+            .filter(Filter::Or(vec![
+                Filter::primary_key(
+                    &["actor_id".to_string(), "first_name".to_string()],
+                    vec![1.into(), "TOM".into()],
+                ),
+                Filter::primary_key(
+                    &["actor_id".to_string(), "first_name".to_string()],
+                    vec![2.into(), "MEG".into()],
+                ),
+            ]))
+            .build(&SqliteDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor \
+             WHERE ((actor_id = ?1) AND (first_name = ?2)) OR ((actor_id = ?3) AND (first_name = ?4))"
+        );
+    }
+
+    #[test]
+    fn select_after_keyset() {
+        let statement = Select::<Actor>::new()
+            .filter(Filter::eq("first_name", "TOM"))
+            .order_by("last_name", Direction::Desc)
+            .order_by("actor_id", Direction::Asc)
+            .after(vec!["HANKS".into(), 10.into()])
+            .limit(20)
+            .build(&PostgresDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor \
+             WHERE (first_name = $1) AND ((last_name < $2) OR ((last_name = $3) AND (actor_id > $4))) \
+             ORDER BY last_name DESC, actor_id ASC LIMIT 20"
+        );
+        assert_eq!(statement.params, vec![
+            Value::Text("TOM".into()),
+            Value::Text("HANKS".into()),
+            Value::Text("HANKS".into()),
+            Value::Int(10)
+        ]);
+    }
+
+    #[test]
+    fn stale_cursor() {
+        let select = Select::<Actor>::new()
+            .order_by("last_name", Direction::Desc)
+            .order_by("actor_id", Direction::Asc);
+        assert!(select.check_cursor().is_ok());
+        assert!(select.clone().after(vec!["HANKS".into(), 10.into()]).check_cursor().is_ok());
+        match select.after(vec![10.into()]).check_cursor() {
+            Err(DbError::DataError(DataError::CursorMismatch { expected, found })) => {
+                assert_eq!((expected, found), (2, 1))
+            }
+            _ => panic!("expecting a cursor mismatch error"),
+        }
+    }
+
+    #[test]
+    fn select_total_and_count() {
+        let select = Select::<Actor>::new()
+            .filter(Filter::gt("actor_id", 10))
+            .order_by("actor_id", Direction::Asc)
+            .limit(20)
+            .offset(40);
+        assert_eq!(
+            select.clone().with_total().build(&PostgresDialect).sql,
+            "SELECT actor_id, first_name, last_name, COUNT(*) OVER() AS rustorm_total \
+             FROM actor WHERE actor_id > $1 ORDER BY actor_id ASC LIMIT 20 OFFSET 40"
+        );
+        let statement = select.build_count(&SqliteDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT COUNT(*) AS count FROM \
+             (SELECT actor_id, first_name, last_name FROM actor WHERE actor_id > ?1) AS rustorm_count"
+        );
+        assert_eq!(statement.params, vec![Value::Int(10)]);
+    }
+
+    #[test]
+    fn negated_and_empty_filters() {
+        let statement = Select::<Actor>::new()
+            .filter(!Filter::is_in::<_, i32>("actor_id", &[]))
+            .build(&PostgresDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor WHERE NOT (FALSE)"
+        );
+        let statement = Select::<Actor>::new()
+            .filter(Filter::Or(vec![]))
+            .build(&SqliteDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT actor_id, first_name, last_name FROM actor WHERE 0"
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        #[derive(ToTableName, ToColumnNames)]
+        #[allow(dead_code)]
+        struct User {
+            user: String,
+            order: i32,
+        }
+        let statement = Select::<User>::new()
+            .filter(Filter::eq("user.order", 1))
+            .offset(5)
+            .build(&SqliteDialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT user, \"order\" FROM user WHERE user.\"order\" = ?1 LIMIT -1 OFFSET 5"
+        );
+        let statement = Select::<User>::new().build(&MysqlDialect);
+        assert_eq!(statement.sql, "SELECT user, `order` FROM user");
+        let statement = Select::<User>::new().build(&PostgresDialect);
+        assert_eq!(statement.sql, "SELECT \"user\", \"order\" FROM \"user\"");
+    }
+}
@@ -12,7 +12,13 @@ use crate::{
         Literal,
     },
     common,
+    database::{
+        self,
+        DaoIter,
+    },
+    dialect::SqliteDialect,
     error::{
+        ConvertError,
         DataOpError,
         PlatformError,
     },
@@ -22,17 +28,22 @@ use crate::{
         SchemaContent,
         TableKey,
     },
+    transaction::TransactionDepth,
     types::SqlType,
     util,
     ColumnName,
+    Dao,
     Database,
     DatabaseName,
     DbError,
+    Dialect,
     FromDao,
+    IsolationLevel,
     Rows,
     TableDef,
     TableName,
     ToValue,
+    TransactionOptions,
     Value,
 };
 
@@ -41,6 +52,7 @@ use r2d2::{
     self,
     ManageConnection,
 };
+use std::mem::ManuallyDrop;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -60,11 +72,20 @@ pub fn test_connection(db_url: &str) -> Result<(), SqliteError> {
     Ok(())
 }
 
-pub struct SqliteDB(pub r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>);
+pub struct SqliteDB(
+    pub r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>,
+    pub(crate) TransactionDepth,
+);
 
-fn to_sq_value(val: &Value) -> rusqlite::types::Value {
+fn to_sq_value(val: &Value) -> Result<rusqlite::types::Value, ConvertError> {
     use bigdecimal::num_traits::ToPrimitive;
-    match *val {
+    let unsupported = || {
+        ConvertError::UnsupportedConversion {
+            value: format!("{:?}", val),
+            to: "a sqlite value".to_string(),
+        }
+    };
+    let sq_value = match *val {
         Value::Text(ref v) => rusqlite::types::Value::Text(v.to_owned()),
         Value::Bool(v) => rusqlite::types::Value::Integer(if v { 1 } else { 0 }),
         Value::Tinyint(v) => rusqlite::types::Value::Integer(i64::from(v)),
@@ -75,9 +96,9 @@ fn to_sq_value(val: &Value) -> rusqlite::types::Value {
         Value::Float(v) => rusqlite::types::Value::Real(f64::from(v)),
         Value::Double(v) => rusqlite::types::Value::Real(v),
         Value::BigDecimal(ref v) => {
-            match v.to_f64() {
-                Some(v) => rusqlite::types::Value::Real(v as f64),
-                None => panic!("unable to convert bigdecimal"),
+            match v.to_f64().filter(|v| v.is_finite()) {
+                Some(v) => rusqlite::types::Value::Real(v),
+                None => return Err(unsupported()),
             }
         }
         Value::Blob(ref v) => rusqlite::types::Value::Blob(v.clone()),
@@ -85,37 +106,153 @@ fn to_sq_value(val: &Value) -> rusqlite::types::Value {
         Value::Json(ref v) => rusqlite::types::Value::Text(v.clone()),
         Value::Uuid(ref v) => rusqlite::types::Value::Text(v.to_string()),
         Value::Date(ref v) => rusqlite::types::Value::Text(v.to_string()),
+        Value::Time(ref v) => rusqlite::types::Value::Text(v.to_string()),
         Value::DateTime(ref v) => rusqlite::types::Value::Text(v.to_string()),
+        // stored the same as a datetime, in utc
+        Value::Timestamp(ref v) => rusqlite::types::Value::Text(v.naive_utc().to_string()),
         Value::Nil => rusqlite::types::Value::Null,
-        _ => panic!("not yet handled: {:?}", val),
+        Value::Interval(_) | Value::Point(_) | Value::Array(_) => return Err(unsupported()),
+    };
+    Ok(sq_value)
+}
+
+fn to_sq_values(params: &[&Value]) -> Result<Vec<rusqlite::types::Value>, ConvertError> {
+    params.iter().map(|param| to_sq_value(param)).collect()
+}
+
+fn row_values(row: &rusqlite::Row, column_count: usize) -> Vec<Value> {
+    let mut record: Vec<Value> = vec![];
+    for i in 0..column_count {
+        let raw = row.get(i);
+        if let Ok(raw) = raw {
+            let value = match raw {
+                rusqlite::types::Value::Blob(v) => Value::Blob(v),
+                rusqlite::types::Value::Real(v) => Value::Double(v),
+                rusqlite::types::Value::Integer(v) => Value::Bigint(v),
+                rusqlite::types::Value::Text(v) => Value::Text(v),
+                rusqlite::types::Value::Null => Value::Nil,
+            };
+            record.push(value);
+        }
     }
+    record
 }
 
-fn to_sq_values(params: &[&Value]) -> Vec<rusqlite::types::Value> {
-    let mut sql_values = Vec::with_capacity(params.len());
-    for param in params {
-        let sq_val = to_sq_value(param);
-        sql_values.push(sq_val);
+/// The rows of a prepared statement which is stepped each time the iterator is advanced.
+/// The statement is boxed so the rows can borrow it for as long as the iterator lives
+struct SqliteRows<'conn> {
+    rows: ManuallyDrop<rusqlite::Rows<'conn>>,
+    stmt: *mut rusqlite::Statement<'conn>,
+    columns: Vec<String>,
+    sql: String,
+}
+
+impl<'conn> SqliteRows<'conn> {
+    fn new(stmt: rusqlite::Statement<'conn>, columns: Vec<String>, sql: String) -> Self {
+        let stmt = Box::into_raw(Box::new(stmt));
+        // SAFETY: the statement is only freed in `drop`, after the rows that borrow it
+        let rows = unsafe { (*stmt).raw_query() };
+        SqliteRows {
+            rows: ManuallyDrop::new(rows),
+            stmt,
+            columns,
+            sql,
+        }
+    }
+}
+
+impl Drop for SqliteRows<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the rows are dropped first, which resets the statement, and are not used
+        // again. The statement was allocated in `new` and nothing else refers to it
+        unsafe {
+            ManuallyDrop::drop(&mut self.rows);
+            drop(Box::from_raw(self.stmt));
+        }
+    }
+}
+
+impl Iterator for SqliteRows<'_> {
+    type Item = Result<Dao, DbError>;
+
+    /// the rows reset the statement at the end or on error, and return no more rows after
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Ok(Some(row)) => {
+                let values = row_values(row, self.columns.len());
+                Some(Ok(database::to_dao(&self.columns, values)))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(SqliteError::Sql(e, self.sql.clone()).into())),
+        }
+    }
+}
+
+/// sqlite transactions are always serializable and it has no read only transactions,
+/// so only the default options are supported
+fn begin_statements(options: &TransactionOptions) -> Result<Vec<String>, DbError> {
+    match options.isolation_level {
+        None | Some(IsolationLevel::Serializable) => (),
+        Some(isolation_level) => {
+            return Err(DbError::UnsupportedOperation(format!(
+                "{} isolation level in sqlite",
+                isolation_level.to_sql()
+            )));
+        }
+    }
+    if options.read_only {
+        return Err(DbError::UnsupportedOperation(
+            "read only transaction in sqlite".to_string(),
+        ));
+    }
+    Ok(vec!["BEGIN TRANSACTION".to_string()])
+}
+
+impl SqliteDB {
+    /// the limit of bound params in a statement of this connection, which is 999 in the builds
+    /// of sqlite older than 3.32 and 32766 after
+    pub fn max_params(&self) -> usize {
+        self.0.limit(rusqlite::limits::Limit::SQLITE_LIMIT_VARIABLE_NUMBER) as usize
     }
-    sql_values
 }
 
 impl Database for SqliteDB {
     fn begin_transaction(&mut self) -> Result<(), DbError> {
-        self.execute_sql_with_return("BEGIN TRANSACTION", &[])?;
+        self.begin_transaction_with_options(&TransactionOptions::default())
+    }
+
+    fn begin_transaction_with_options(
+        &mut self,
+        options: &TransactionOptions,
+    ) -> Result<(), DbError> {
+        for sql in self.1.begin_sql(options, begin_statements)? {
+            self.execute_sql(&sql, &[])?;
+        }
+        self.1.begun();
         Ok(())
     }
 
     fn commit_transaction(&mut self) -> Result<(), DbError> {
-        self.execute_sql_with_return("COMMIT TRANSACTION", &[])?;
-        Ok(())
+        let sql = self.1.commit_sql("COMMIT TRANSACTION");
+        let result = self.execute_sql(&sql, &[]);
+        self.1.ended(result.is_ok());
+        result.map(|_| ())
     }
 
     fn rollback_transaction(&mut self) -> Result<(), DbError> {
-        self.execute_sql_with_return("ROLLBACK TRANSACTION", &[])?;
-        Ok(())
+        let mut result = Ok(0);
+        for sql in self.1.rollback_sql("ROLLBACK TRANSACTION") {
+            result = self.execute_sql(&sql, &[]);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.1.ended(result.is_ok());
+        result.map(|_| ())
     }
 
+    fn transaction_depth(&self) -> usize { self.1.get() }
+
     fn execute_sql_with_return(&mut self, sql: &str, params: &[&Value]) -> Result<Rows, DbError> {
         info!("executing sql: {}", sql);
         println!("executing sql: {}", sql);
@@ -132,39 +269,55 @@ impl Database for SqliteDB {
 
         match stmt {
             Ok(mut stmt) => {
-                let sq_values = to_sq_values(params);
+                let sq_values = to_sq_values(params)?;
                 let column_count = stmt.column_count();
                 let mut records = Rows::new(column_names);
-                if let Ok(mut rows) = stmt.query(rusqlite::params_from_iter(&sq_values)) {
-                    while let Some(row) = rows.next()? {
-                        let mut record: Vec<Value> = vec![];
-                        for i in 0..column_count {
-                            let raw = row.get(i);
-                            if let Ok(raw) = raw {
-                                let value = match raw {
-                                    rusqlite::types::Value::Blob(v) => Value::Blob(v),
-                                    rusqlite::types::Value::Real(v) => Value::Double(v),
-                                    rusqlite::types::Value::Integer(v) => Value::Bigint(v),
-                                    rusqlite::types::Value::Text(v) => Value::Text(v),
-                                    rusqlite::types::Value::Null => Value::Nil,
-                                };
-                                record.push(value);
-                            }
-                        }
-                        records.push(record);
-                    }
+                let mut rows = stmt
+                    .query(rusqlite::params_from_iter(&sq_values))
+                    .map_err(|e| SqliteError::Sql(e, sql.to_string()))?;
+                while let Some(row) = rows
+                    .next()
+                    .map_err(|e| SqliteError::Sql(e, sql.to_string()))?
+                {
+                    records.push(row_values(row, column_count));
                 }
                 Ok(records)
             }
             Err(e) => {
                 Err(
-                    Into::<DataOpError>::into(PlatformError::SqliteError(SqliteError::SqlError(e)))
-                        .into(),
+                    Into::<DataOpError>::into(PlatformError::SqliteError(SqliteError::Sql(
+                        e,
+                        sql.to_string(),
+                    )))
+                    .into(),
                 )
             }
         }
     }
 
+    fn query_iter<'a>(&'a mut self, sql: &str, params: &[&Value]) -> Result<DaoIter<'a>, DbError> {
+        info!("executing sql: {}", sql);
+        info!("params: {:?}", params);
+        let sql_error = |e| SqliteError::Sql(e, sql.to_string());
+        let mut stmt = self.0.prepare(sql).map_err(sql_error)?;
+        for (i, value) in to_sq_values(params)?.into_iter().enumerate() {
+            stmt.raw_bind_parameter(i + 1, value).map_err(sql_error)?;
+        }
+        let columns = stmt.column_names().iter().map(ToString::to_string).collect();
+        Ok(Box::new(SqliteRows::new(stmt, columns, sql.to_string())))
+    }
+
+    fn execute_sql(&mut self, sql: &str, params: &[&Value]) -> Result<u64, DbError> {
+        info!("executing sql: {}", sql);
+        info!("params: {:?}", params);
+        let sq_values = to_sq_values(params)?;
+        let affected = self
+            .0
+            .execute(sql, rusqlite::params_from_iter(&sq_values))
+            .map_err(|e| SqliteError::Sql(e, sql.to_string()))?;
+        Ok(affected as u64)
+    }
+
     #[allow(unused_variables)]
     fn get_table(&mut self, table_name: &TableName) -> Result<Option<TableDef>, DbError> {
         #[derive(Debug)]
@@ -346,7 +499,7 @@ impl Database for SqliteDB {
                 }
             };
         }
-        let sql = format!("PRAGMA table_info({});", table_name.complete_name());
+        let sql = table_pragma("table_info", table_name);
         let result = self.execute_sql_with_return(&sql, &[])?;
         let mut primary_columns = vec![];
         let mut columns = vec![];
@@ -357,10 +510,11 @@ impl Database for SqliteDB {
             let data_type = unwrap_ok_some!(data_type).to_lowercase();
             let not_null: Result<Option<i64>, _> = dao.get("notnull");
             let not_null = unwrap_ok_some!(not_null) != 0;
+            // the position of the column in the primary key, 0 if it is not part of it
             let pk: Result<Option<i64>, _> = dao.get("pk");
-            let pk = unwrap_ok_some!(pk) != 0;
-            if pk {
-                primary_columns.push(ColumnName::from(&name));
+            let pk = unwrap_ok_some!(pk);
+            if pk != 0 {
+                primary_columns.push((pk, ColumnName::from(&name)));
             }
             let default = dao.0.get("dflt_value").map(|v| {
                 match *v {
@@ -377,9 +531,13 @@ impl Database for SqliteDB {
             };
             columns.push(simple.to_column(table_name));
         }
+        primary_columns.sort_by_key(|(position, _)| *position);
         let primary_key = Key {
             name: None,
-            columns: primary_columns,
+            columns: primary_columns
+                .into_iter()
+                .map(|(_, column)| column)
+                .collect(),
         };
         info!("primary key: {:#?}", primary_key);
         let foreign_keys = get_foreign_keys(&mut *self, table_name)?;
@@ -521,9 +679,26 @@ fn get_table_names(db: &mut dyn Database, kind: &str) -> Result<Vec<TableName>,
     Ok(table_names)
 }
 
+/// the pragma on the table, the schema of the table is written before the pragma
+/// as in `PRAGMA main.table_info(actor)`
+fn table_pragma(pragma: &str, table: &TableName) -> String {
+    let name = SqliteDialect.safe_identifier(&table.name);
+    match table.schema {
+        Some(ref schema) => {
+            format!(
+                "PRAGMA {}.{}({});",
+                SqliteDialect.safe_identifier(schema),
+                pragma,
+                name
+            )
+        }
+        None => format!("PRAGMA {}({});", pragma, name),
+    }
+}
+
 /// get the foreign keys of table
 fn get_foreign_keys(db: &mut dyn Database, table: &TableName) -> Result<Vec<ForeignKey>, DbError> {
-    let sql = format!("PRAGMA foreign_key_list({});", table.complete_name());
+    let sql = table_pragma("foreign_key_list", table);
     #[derive(Debug, FromDao)]
     struct ForeignSimple {
         id: i64,
@@ -570,6 +745,8 @@ fn get_foreign_keys(db: &mut dyn Database, table: &TableName) -> Result<Vec<Fore
 pub enum SqliteError {
     #[error("Error executing {0}")]
     SqlError(#[from] rusqlite::Error),
+    #[error("Error executing {1}: {0}")]
+    Sql(rusqlite::Error, String),
     #[error("Pool initialization error: {0}")]
     PoolInitializationError(#[from] r2d2::Error),
 }
@@ -598,6 +775,54 @@ mod test {
         },
     };
 
+    #[test]
+    fn unsupported_sqlite_params() {
+        use bigdecimal::BigDecimal;
+        use std::str::FromStr;
+
+        let huge = BigDecimal::from_str("1e400").unwrap();
+        assert!(to_sq_value(&Value::BigDecimal(huge)).is_err());
+        let interval = Value::Interval(rustorm_dao::Interval::new(1, 0, 0));
+        assert!(to_sq_value(&interval).is_err());
+        let point = Value::Point(geo_types::Point::new(1.0, 2.0));
+        assert!(to_sq_value(&point).is_err());
+        let array = vec![1, 2].to_value();
+        assert!(to_sq_values(&[&Value::Int(1), &array]).is_err());
+    }
+
+    #[test]
+    fn unsupported_param_is_an_error() {
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let interval = Value::Interval(rustorm_dao::Interval::new(1, 0, 0));
+        let result = em.db().execute_sql_with_return("SELECT ?1", &[&interval]);
+        assert!(matches!(
+            result,
+            Err(DbError::ConvertError(ConvertError::UnsupportedConversion { .. }))
+        ));
+    }
+
+    #[test]
+    fn query_iter_streams_the_records() {
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let sql = "SELECT actor_id, first_name FROM actor WHERE actor_id <= ?1 ORDER BY actor_id";
+        let ids: Vec<i32> = em
+            .db()
+            .query_iter(sql, &[&3.into()])
+            .unwrap()
+            .map(|dao| dao.unwrap().get("actor_id").unwrap())
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        // the statement is reset when the iterator is dropped before the end
+        let first = em.db().query_iter(sql, &[&3.into()]).unwrap().next();
+        let first: String = first.unwrap().unwrap().get("first_name").unwrap();
+        assert_eq!(first, "PENELOPE");
+        let count = em.db().query_iter(sql, &[&2.into()]).unwrap().count();
+        assert_eq!(count, 2);
+    }
+
     #[test]
     fn test_advancing_autoincrement_primary_column() {
         let db_url = "sqlite://sakila.db";
@@ -621,6 +846,243 @@ mod test {
         println!("last value: {}", last_value);
     }
 
+    #[test]
+    fn test_transaction_rollback_on_drop() {
+        let db_url = "sqlite://sakila.db";
+        let mut pool = Pool::new();
+        let mut em = pool.em(db_url).unwrap();
+        let actor_table = TableName::from("actor");
+        let count = em.get_total_records(&actor_table).unwrap();
+        {
+            let mut tx = em.transaction().unwrap();
+            let deleted = tx
+                .db()
+                .execute_sql("DELETE FROM actor WHERE actor_id = $1", &[&1.into()])
+                .unwrap();
+            assert_eq!(deleted, 1);
+            assert_eq!(tx.get_total_records(&actor_table).unwrap(), count - 1);
+        }
+        assert_eq!(em.get_total_records(&actor_table).unwrap(), count);
+    }
+
+    #[test]
+    fn insert_in_chunks() {
+        use crate::codegen::{
+            FromDao,
+            ToColumnNames,
+            ToDao,
+            ToPrimaryKey,
+            ToTableName,
+        };
+
+        #[derive(Debug, FromDao, ToDao, ToTableName, ToColumnNames, ToPrimaryKey)]
+        struct Actor {
+            #[rustorm(primary_key)]
+            actor_id: i32,
+            first_name: String,
+            last_name: String,
+            last_update: String,
+        }
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let mut tx = em.transaction().unwrap();
+        // more params than a statement can have, in the reverse order of the keys
+        let actors: Vec<Actor> = (0..1000)
+            .rev()
+            .map(|i| {
+                Actor {
+                    actor_id: 10_000 + i,
+                    first_name: format!("FIRST{}", i),
+                    last_name: "CHUNK".to_string(),
+                    last_update: "2006-02-15 04:34:33".to_string(),
+                }
+            })
+            .collect();
+        let inserted: Vec<Actor> = tx.insert(&actors.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(inserted.len(), actors.len());
+        for (inserted, actor) in inserted.iter().zip(&actors) {
+            assert_eq!(inserted.actor_id, actor.actor_id);
+            assert_eq!(inserted.first_name, actor.first_name);
+        }
+    }
+
+    #[test]
+    fn update_and_delete_where() {
+        use crate::{
+            codegen::ToTableName,
+            Filter,
+        };
+
+        #[derive(ToTableName)]
+        struct Actor;
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let mut tx = em.transaction().unwrap();
+        for i in 0..5 {
+            tx.db()
+                .execute_sql(
+                    "INSERT INTO actor(actor_id, first_name, last_name, last_update) \
+                     VALUES(?, ?, 'WHERE', '2006-02-15 04:34:33')",
+                    &[&Value::Int(20_000 + i), &Value::Text(format!("FIRST{}", i))],
+                )
+                .unwrap();
+        }
+        let mut set = Dao::new();
+        set.insert("first_name", "UPDATED");
+        let filter = Filter::eq("last_name", "WHERE").and(Filter::lt("actor_id", 20_003));
+        assert_eq!(tx.update_where::<Actor>(set, filter).unwrap(), 3);
+        let filter = Filter::eq("first_name", "UPDATED");
+        assert_eq!(tx.delete_where::<Actor>(filter.clone()).unwrap(), 3);
+        assert_eq!(tx.delete_where::<Actor>(filter).unwrap(), 0);
+    }
+
+    #[test]
+    fn paginate_rejects_empty_pages() {
+        use crate::{
+            codegen::{
+                FromDao,
+                ToColumnNames,
+                ToTableName,
+            },
+            error::DataError,
+            Select,
+        };
+
+        #[derive(Debug, FromDao, ToTableName, ToColumnNames)]
+        #[allow(dead_code)]
+        struct Actor {
+            actor_id: i32,
+        }
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let select = Select::<Actor>::new();
+        assert!(matches!(
+            em.paginate(&select, 0, 10),
+            Err(DbError::DataError(DataError::PageOutOfRange(0)))
+        ));
+        assert!(matches!(
+            em.paginate(&select, 1, 0),
+            Err(DbError::DataError(DataError::InvalidPerPage(0)))
+        ));
+        assert!(matches!(
+            em.paginate(&select, usize::MAX, 10),
+            Err(DbError::DataError(DataError::PageOutOfRange(usize::MAX)))
+        ));
+    }
+
+    #[test]
+    fn insert_without_primary_key() {
+        use crate::codegen::{
+            FromDao,
+            ToColumnNames,
+            ToDao,
+            ToPrimaryKey,
+            ToTableName,
+        };
+
+        #[derive(Debug, FromDao, ToDao, ToTableName, ToColumnNames, ToPrimaryKey)]
+        struct Note {
+            body: String,
+            author: String,
+        }
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let mut tx = em.transaction().unwrap();
+        tx.db().execute_sql("CREATE TABLE note(body text, author text)", &[]).unwrap();
+        let notes: Vec<Note> = (0..3)
+            .map(|i| {
+                Note {
+                    body: format!("NOTE{}", i),
+                    author: "SAME".to_string(),
+                }
+            })
+            .collect();
+        let inserted: Vec<Note> = tx.insert(&notes.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(inserted.len(), notes.len());
+        for (inserted, note) in inserted.iter().zip(&notes) {
+            assert_eq!(inserted.body, note.body);
+        }
+    }
+
+    #[test]
+    fn insert_with_generated_keys() {
+        use crate::codegen::{
+            FromDao,
+            ToColumnNames,
+            ToDao,
+            ToPrimaryKey,
+            ToTableName,
+        };
+
+        #[derive(Debug, FromDao, ToDao, ToTableName, ToColumnNames, ToPrimaryKey)]
+        struct Note {
+            #[rustorm(primary_key, generated)]
+            note_id: i32,
+            body: String,
+        }
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let mut tx = em.transaction().unwrap();
+        tx.db()
+            .execute_sql("CREATE TABLE note(note_id integer PRIMARY KEY, body text)", &[])
+            .unwrap();
+        tx.db().execute_sql("INSERT INTO note VALUES(10, 'FIRST')", &[]).unwrap();
+        let notes: Vec<Note> = (0..3)
+            .map(|i| {
+                Note {
+                    note_id: 0,
+                    body: format!("NOTE{}", i),
+                }
+            })
+            .collect();
+        let inserted: Vec<Note> = tx.insert(&notes.iter().collect::<Vec<_>>()).unwrap();
+        let inserted: Vec<(i32, &str)> =
+            inserted.iter().map(|note| (note.note_id, note.body.as_str())).collect();
+        assert_eq!(inserted, vec![(11, "NOTE0"), (12, "NOTE1"), (13, "NOTE2")]);
+    }
+
+    #[test]
+    fn find_and_delete_by_declared_primary_key() {
+        use crate::codegen::{
+            FromDao,
+            ToColumnNames,
+            ToDao,
+            ToPrimaryKey,
+            ToTableName,
+        };
+
+        #[derive(Debug, FromDao, ToDao, ToTableName, ToColumnNames, ToPrimaryKey)]
+        struct Note {
+            #[rustorm(primary_key)]
+            note_id: i32,
+            body: String,
+        }
+
+        let mut pool = Pool::new();
+        let mut em = pool.em("sqlite://sakila.db").unwrap();
+        let mut tx = em.transaction().unwrap();
+        tx.db().execute_sql("CREATE TABLE note(note_id integer, body text)", &[]).unwrap();
+        let notes: Vec<Note> = (1..=3)
+            .map(|i| {
+                Note {
+                    note_id: i,
+                    body: format!("NOTE{}", i),
+                }
+            })
+            .collect();
+        let _: Vec<Note> = tx.insert(&notes.iter().collect::<Vec<_>>()).unwrap();
+        let found: Vec<Note> = tx.find_by_ids(&[1, 3]).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(tx.delete(&notes[1]).unwrap(), 1);
+        let found: Option<Note> = tx.find_by_id(2).unwrap();
+        assert!(found.is_none());
+    }
+
     #[test]
     fn test_get_all_tables() {
         let db_url = "sqlite://sakila.db";
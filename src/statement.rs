@@ -0,0 +1,52 @@
+use crate::{
+    Dialect,
+    Value,
+};
+
+/// An sql statement where the arguments are kept separate from the sql text.
+/// The arguments are passed to the database as bound parameters, this is an easy way
+/// to mitigate sql injection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl Statement {
+    /// the params borrowed in the form accepted by `Database::execute_sql_with_return`
+    pub fn param_refs(&self) -> Vec<&Value> { self.params.iter().collect() }
+}
+
+/// accumulates the sql text and the params that are bound to it
+pub(crate) struct SqlWriter<'a> {
+    pub(crate) dialect: &'a dyn Dialect,
+    statement: Statement,
+}
+
+impl<'a> SqlWriter<'a> {
+    pub(crate) fn new(dialect: &'a dyn Dialect) -> Self {
+        SqlWriter {
+            dialect,
+            statement: Statement {
+                sql: String::new(),
+                params: vec![],
+            },
+        }
+    }
+
+    pub(crate) fn push(&mut self, sql: &str) { self.statement.sql += sql; }
+
+    /// write the identifier, quoted if needed
+    pub(crate) fn push_identifier(&mut self, ident: &str) {
+        self.statement.sql += &self.dialect.safe_identifier(ident);
+    }
+
+    /// add the value as a bound param and write its placeholder into the sql
+    pub(crate) fn push_param(&mut self, value: Value) {
+        self.statement.params.push(value);
+        let index = self.statement.params.len();
+        self.statement.sql += &self.dialect.placeholder(index);
+    }
+
+    pub(crate) fn finish(self) -> Statement { self.statement }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    DbError,
+    EntityManager,
+};
+use log::*;
+use std::{
+    ops::{
+        Deref,
+        DerefMut,
+    },
+    time::Duration,
+};
+
+/// A transaction on the connection of the entity manager, the queries are
+/// executed through it the same way as with the `EntityManager`.
+///
+/// The transaction is rolled back when it is dropped without calling `commit`,
+/// such as when returning early with `?`, so the connection is never returned
+/// to the pool with an open transaction.
+///
+/// ```rust,ignore
+/// let mut tx = em.transaction()?;
+/// tx.update(&actor)?;
+/// tx.delete(&old_actor)?;
+/// tx.commit()?;
+/// ```
+pub struct Transaction<'a> {
+    em: &'a mut EntityManager,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(
+        em: &'a mut EntityManager,
+        options: &TransactionOptions,
+    ) -> Result<Self, DbError> {
+        em.db().begin_transaction_with_options(options)?;
+        Ok(Transaction {
+            em,
+            finished: false,
+        })
+    }
+
+    /// commit the transaction, if the commit fails the transaction is rolled back
+    pub fn commit(mut self) -> Result<(), DbError> {
+        self.em.commit_transaction()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    pub fn rollback(mut self) -> Result<(), DbError> {
+        self.finished = true;
+        self.em.rollback_transaction()
+    }
+}
+
+impl Deref for Transaction<'_> {
+    type Target = EntityManager;
+
+    fn deref(&self) -> &Self::Target { self.em }
+}
+
+impl DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target { self.em }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Err(e) = self.em.rollback_transaction() {
+                error!("Unable to rollback the transaction: {}", e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub(crate) fn to_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+/// The characteristics of a transaction, the default is the isolation level
+/// and access mode of the database.
+///
+/// ```rust,ignore
+/// let options = TransactionOptions::default()
+///     .isolation_level(IsolationLevel::RepeatableRead)
+///     .read_only();
+/// let tx = em.transaction_with_options(&options)?;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransactionOptions {
+    pub isolation_level: Option<IsolationLevel>,
+    pub read_only: bool,
+}
+
+impl TransactionOptions {
+    pub fn isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+}
+
+/// How many times and how long apart a transaction is run again when it fails with
+/// an error that is safe to retry, see `DbError::is_retryable`.
+/// The wait doubles after every attempt, up to `max_backoff`.
+///
+/// ```rust,ignore
+/// let policy = RetryPolicy {
+///     max_retries: 5,
+///     ..Default::default()
+/// };
+/// em.run_in_transaction_with(&TransactionOptions::default(), &policy, |tx| {
+///     tx.update(&actor)
+/// })?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// never retry
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// the time to wait before the retry `attempt`, starting at 1
+    pub(crate) fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+/// The number of transactions that are open on a connection.
+/// A transaction that is begun inside another one is a savepoint, so it can be
+/// rolled back without aborting the outer transaction.
+#[derive(Debug, Default)]
+pub(crate) struct TransactionDepth(usize);
+
+impl TransactionDepth {
+    pub(crate) fn get(&self) -> usize { self.0 }
+
+    /// the statements that begin a transaction with the options, or a savepoint when there is
+    /// a transaction already. A savepoint can not have options of its own, it has the
+    /// characteristics of the outer transaction.
+    pub(crate) fn begin_sql<F>(
+        &self,
+        options: &TransactionOptions,
+        begin: F,
+    ) -> Result<Vec<String>, DbError>
+    where
+        F: FnOnce(&TransactionOptions) -> Result<Vec<String>, DbError>,
+    {
+        match self.0 {
+            0 => begin(options),
+            depth if *options == TransactionOptions::default() => {
+                Ok(vec![format!("SAVEPOINT {}", savepoint(depth))])
+            }
+            _ => {
+                Err(DbError::UnsupportedOperation(
+                    "a nested transaction can not have its own isolation level or access mode"
+                        .to_string(),
+                ))
+            }
+        }
+    }
+
+    /// the statement that commits the transaction, or releases the innermost savepoint
+    pub(crate) fn commit_sql(&self, commit: &str) -> String {
+        match self.0 {
+            0 | 1 => commit.to_string(),
+            depth => format!("RELEASE SAVEPOINT {}", savepoint(depth - 1)),
+        }
+    }
+
+    /// the statements that rollback the transaction, or the changes made since the innermost
+    /// savepoint which is then released
+    pub(crate) fn rollback_sql(&self, rollback: &str) -> Vec<String> {
+        match self.0 {
+            0 | 1 => vec![rollback.to_string()],
+            depth => {
+                vec![
+                    format!("ROLLBACK TO SAVEPOINT {}", savepoint(depth - 1)),
+                    format!("RELEASE SAVEPOINT {}", savepoint(depth - 1)),
+                ]
+            }
+        }
+    }
+
+    /// called when the transaction or savepoint is begun
+    pub(crate) fn begun(&mut self) { self.0 += 1; }
+
+    /// called after the commit or rollback. The outermost transaction is always considered
+    /// ended, while a savepoint is only removed when it is released successfully so the outer
+    /// transaction can still roll it back.
+    pub(crate) fn ended(&mut self, succeeded: bool) {
+        if self.0 <= 1 {
+            self.0 = 0;
+        } else if succeeded {
+            self.0 -= 1;
+        }
+    }
+}
+
+/// the name of the savepoint that is begun inside `level` open transactions
+fn savepoint(level: usize) -> String { format!("rustorm_savepoint_{}", level) }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_transactions_use_savepoints() {
+        let options = TransactionOptions::default();
+        let begin = |_: &TransactionOptions| Ok(vec!["BEGIN".to_string()]);
+        let mut depth = TransactionDepth::default();
+        assert_eq!(depth.begin_sql(&options, begin).unwrap(), vec!["BEGIN"]);
+        depth.begun();
+        assert_eq!(depth.begin_sql(&options, begin).unwrap(), vec![
+            "SAVEPOINT rustorm_savepoint_1"
+        ]);
+        depth.begun();
+        assert!(depth
+            .begin_sql(&options.read_only(), begin)
+            .is_err());
+        assert_eq!(depth.begin_sql(&options, begin).unwrap(), vec![
+            "SAVEPOINT rustorm_savepoint_2"
+        ]);
+        depth.begun();
+        assert_eq!(depth.get(), 3);
+
+        assert_eq!(depth.rollback_sql("ROLLBACK"), vec![
+            "ROLLBACK TO SAVEPOINT rustorm_savepoint_2",
+            "RELEASE SAVEPOINT rustorm_savepoint_2"
+        ]);
+        depth.ended(true);
+        assert_eq!(
+            depth.commit_sql("COMMIT"),
+            "RELEASE SAVEPOINT rustorm_savepoint_1"
+        );
+        // a failed release keeps the savepoint, so it can still be rolled back
+        depth.ended(false);
+        assert_eq!(depth.get(), 2);
+        depth.ended(true);
+        assert_eq!(depth.commit_sql("COMMIT"), "COMMIT");
+        depth.ended(false);
+        assert_eq!(depth.get(), 0);
+    }
+
+    #[test]
+    fn retry_backoff_doubles_up_to_the_max() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(10));
+        assert_eq!(policy.backoff(2), Duration::from_millis(20));
+        assert_eq!(policy.backoff(3), Duration::from_millis(40));
+        assert_eq!(policy.backoff(4), Duration::from_millis(50));
+        assert_eq!(policy.backoff(100), Duration::from_millis(50));
+    }
+}
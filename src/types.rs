@@ -145,9 +145,22 @@ impl HasType for Value {
             Value::Timestamp(_) => Some(SqlType::Timestamp),
             Value::Interval(_) => Some(SqlType::Interval),
             Value::Point(_) => Some(SqlType::Point),
-            Value::Array(Array::Int(_)) => Some(SqlType::Array(Box::new(SqlType::Int))),
-            Value::Array(Array::Float(_)) => Some(SqlType::Array(Box::new(SqlType::Float))),
-            Value::Array(Array::Text(_)) => Some(SqlType::Array(Box::new(SqlType::Text))),
+            Value::Array(array) => {
+                let element_type = match array {
+                    Array::Bool(_) => SqlType::Bool,
+                    Array::Smallint(_) => SqlType::Smallint,
+                    Array::Int(_) => SqlType::Int,
+                    Array::Bigint(_) => SqlType::Bigint,
+                    Array::Float(_) => SqlType::Float,
+                    Array::Double(_) => SqlType::Double,
+                    Array::BigDecimal(_) => SqlType::Numeric,
+                    Array::Text(_) => SqlType::Text,
+                    Array::Uuid(_) => SqlType::Uuid,
+                    Array::Date(_) => SqlType::Date,
+                    Array::Timestamp(_) => SqlType::Timestamp,
+                };
+                Some(SqlType::Array(Box::new(element_type)))
+            }
         }
     }
 }